@@ -0,0 +1,50 @@
+// Scanning time should grow linearly with the size of the source. Criterion reports throughput
+// per size, which stays flat when it does:
+//
+// cargo bench --bench scanner
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use roxy::scanner::Scanner;
+
+// A script of `lines` lines mixing every kind of token, non-ASCII strings and comments included
+fn script(lines: usize) -> String {
+    let chunk = [
+        "// Ünïcödé comment about the next function",
+        "fun fib_{i}(n) {",
+        "  if (n <= 1) return n; /* nested /* block */ comment */",
+        "  var greeting = \"héllo, 世界 🦀\";",
+        "  return fib_{i}(n - 2) + fib_{i}(n - 1) * 3.25 / 1.5;",
+        "}",
+        "var xs_{i} = [1, 2, 3, {\"k\": true, \"v\": nil}];",
+        "print fib_{i}(10) >= 55 and !false or xs_{i}[0] != 2;",
+    ];
+
+    let mut source = String::new();
+    for i in 0..lines {
+        source.push_str(&chunk[i % chunk.len()].replace("{i}", &(i / chunk.len()).to_string()));
+        source.push('\n');
+    }
+
+    source
+}
+
+fn scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scan_tokens");
+    for lines in [1_000, 10_000, 50_000] {
+        let source = script(lines);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(lines), &source, |b, source| {
+            b.iter(|| {
+                let mut scanner = Scanner::new(black_box(source.clone()));
+                scanner.scan_tokens().unwrap().len()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = scan
+}
+criterion_main!(benches);
@@ -1,9 +1,9 @@
 use core::fmt::Debug;
-use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::{
     environment::Environment,
-    interpreter::Interpreter,
+    interpreter::{ControlFlow, Interpreter},
     utils::errors::{InterpreterError, RoxyError},
     RoxyInstance, RoxyType, Token,
 };
@@ -14,7 +14,6 @@ pub trait Callable: Debug {
         interpreter: &mut Interpreter,
         arguments: Vec<RoxyType>,
         token: Token,
-        closure: Box<Environment>,
     ) -> Result<RoxyType, RoxyError>;
 
     fn arity(&self, token: Token) -> Result<usize, RoxyError>;
@@ -26,64 +25,49 @@ impl Callable for RoxyType {
         interpreter: &mut Interpreter,
         arguments: Vec<RoxyType>,
         token: Token,
-        closure: Box<Environment>,
     ) -> Result<RoxyType, RoxyError> {
         match self {
             RoxyType::RoxyFunction(roxy_fn) => {
-                interpreter.set_is_calling(true);
-                if roxy_fn.is_initializer {
-                    return closure.get_at(0, "this".into());
-                }
-
-                let mut fn_env;
-                if roxy_fn.is_method {
-                    fn_env = roxy_fn.closure.clone().unwrap();
-                } else {
-                    fn_env = Box::new(Environment::new_with_enclosing(closure));
-
-                    if let Some(fn_closure) = roxy_fn.closure.clone() {
-                        for (_, (name, roxy_type)) in fn_closure.values.iter().enumerate() {
-                            fn_env.define(
-                                name.to_owned(),
-                                roxy_type.to_owned(),
-                                Some(&mut interpreter.globals),
-                            );
-                        }
-                    }
-                }
+                let fn_env = Environment::new_ref(Some(roxy_fn.closure.clone()));
 
                 for (i, param) in roxy_fn.params.iter().enumerate() {
                     // Here arguments vector is directly accessed because we can guarantee it won't overflow or underflow as that check is already in interpreter
-                    fn_env.define(
-                        param.lexeme.clone(),
-                        arguments[i].clone(),
-                        Some(&mut interpreter.globals),
-                    );
+                    fn_env
+                        .borrow_mut()
+                        .define(param.lexeme.clone(), arguments[i].clone());
                 }
 
-                let ret_value;
-                if let Some(value) = interpreter.execute_block(roxy_fn.body.clone(), fn_env)? {
-                    ret_value = value;
-                } else {
-                    ret_value = RoxyType::NULL;
+                // Globals in the body belong to the module the function was declared in
+                let previous_globals =
+                    std::mem::replace(&mut interpreter.globals, roxy_fn.globals.clone());
+                let ret_value = interpreter.execute_block(roxy_fn.body.clone(), fn_env);
+                interpreter.globals = previous_globals;
+                if ret_value.is_err() {
+                    interpreter.push_frame(roxy_fn, token.line);
+                }
+                let ret_value = ret_value?;
+
+                // Initializers always hand back the instance, even on an early bare `return;`
+                if roxy_fn.is_initializer {
+                    return roxy_fn.closure.borrow().get_at(0, "this".into());
                 }
 
-                interpreter.set_is_calling(false);
-                return Ok(ret_value);
+                match ret_value {
+                    ControlFlow::Return(value) => return Ok(value),
+                    // The resolver keeps `break` and `continue` from leaving a function body
+                    _ => return Ok(RoxyType::NULL),
+                }
+            }
+            RoxyType::NativeFunction(native_fn) => {
+                (native_fn.callable)(interpreter, arguments, token)
             }
-            //TODO: Implement for NativeFunction
-            RoxyType::NativeFunction(_) => Ok(self.to_owned()),
             RoxyType::RoxyClass(roxy_class) => {
-                let instance = RoxyInstance {
-                    klass: roxy_class.to_owned(),
-                    fields: HashMap::new(),
-                };
+                let instance = RoxyInstance::new(roxy_class.clone());
                 if let Some(initializer) = roxy_class.find_method("init".into()) {
-                    RoxyType::RoxyFunction(initializer.bind(&instance)).call(
+                    RoxyType::RoxyFunction(Rc::new(initializer.bind(&instance))).call(
                         interpreter,
                         arguments,
                         token,
-                        closure,
                     )?;
                 }
 
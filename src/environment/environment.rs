@@ -2,30 +2,20 @@ use crate::{
     utils::errors::{EnvironmentError, RoxyError},
     RoxyType,
 };
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-#[derive(Debug, Clone, PartialEq)]
+// Environments are shared between the interpreter, closures and bound methods, so they live on
+// the heap behind `Rc<RefCell<..>>` and every holder sees writes made by the others
+pub type EnvRef = Rc<RefCell<Environment>>;
+
+#[derive(Debug, Default)]
 pub struct Environment {
     //TODO: Make this private
     pub values: HashMap<String, RoxyType>,
-    pub enclosing: Option<Box<Environment>>,
+    pub enclosing: Option<EnvRef>,
 }
 
-// impl fmt::Display for Environment {
-//     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-//         for value in self.values.values() {
-
-//         }
-
-//         write!(
-//             f,
-//             "{:?}",
-//             self.values.values().clone().collect().to_string()
-//         )
-//     }
-// }
-
-impl<'env> Environment {
+impl Environment {
     pub fn new() -> Self {
         Self {
             values: HashMap::new(),
@@ -33,20 +23,21 @@ impl<'env> Environment {
         }
     }
 
-    pub fn new_with_enclosing(enclosing: Box<Environment>) -> Self {
+    pub fn new_with_enclosing(enclosing: EnvRef) -> Self {
         Self {
             enclosing: Some(enclosing),
             values: HashMap::new(),
         }
     }
 
-    pub fn define(&mut self, name: String, value: RoxyType, globals_opt: Option<&mut Environment>) {
-        if self.enclosing == None {
-            if let Some(globals) = globals_opt {
-                globals.values.insert(name.clone(), value.clone());
-            }
-        }
+    pub fn new_ref(enclosing: Option<EnvRef>) -> EnvRef {
+        Rc::new(RefCell::new(Self {
+            values: HashMap::new(),
+            enclosing,
+        }))
+    }
 
+    pub fn define(&mut self, name: String, value: RoxyType) {
         self.values.insert(name, value);
     }
 
@@ -55,7 +46,7 @@ impl<'env> Environment {
             Some(val) => Ok(val.to_owned()),
             None => {
                 if let Some(enclosing) = &self.enclosing {
-                    return enclosing.get(name);
+                    return enclosing.borrow().get(name);
                 }
 
                 return Err(RoxyError::EnvironmentError(
@@ -66,87 +57,78 @@ impl<'env> Environment {
     }
 
     pub fn get_at(&self, distance: usize, name: String) -> Result<RoxyType, RoxyError> {
-        let env = self.ancestor(distance)?;
-
-        match env.values.get(&name) {
-            Some(value) => {
-                return Ok(value.to_owned());
-            }
-            None => self.get(name),
+        let value = if distance == 0 {
+            self.values.get(&name).cloned()
+        } else {
+            self.ancestor(distance)?.borrow().values.get(&name).cloned()
+        };
+
+        match value {
+            Some(value) => Ok(value),
+            None => Err(RoxyError::EnvironmentError(
+                EnvironmentError::UndefinedVariable(name),
+            )),
         }
     }
 
-    fn ancestor(&self, distance: usize) -> Result<Environment, RoxyError> {
-        let mut env = self.clone();
-        let mut i = 0;
-        loop {
-            if i == distance {
-                break;
+    // Returns the environment `distance` hops up the chain, `distance` must be atleast 1 as the
+    // current environment is not reachable through an `EnvRef` from `&self`
+    fn ancestor(&self, distance: usize) -> Result<EnvRef, RoxyError> {
+        // Here we trust resolver has done it's work well in determining distance
+        let mut env = match &self.enclosing {
+            Some(enclosing) => enclosing.clone(),
+            None => {
+                return Err(RoxyError::EnvironmentError(
+                    EnvironmentError::EnvironmentDoesNotExistAtGivenDistance,
+                ));
             }
-            // Here we trust resolver has done it's work well in determining distance
-            match env.enclosing {
-                Some(environment) => {
-                    env = *environment;
-                }
+        };
+
+        for _ in 1..distance {
+            let enclosing = env.borrow().enclosing.clone();
+            match enclosing {
+                Some(enclosing) => env = enclosing,
                 None => {
                     return Err(RoxyError::EnvironmentError(
                         EnvironmentError::EnvironmentDoesNotExistAtGivenDistance,
                     ));
                 }
             }
-            // env = *env.enclosing.unwrap();
-
-            i = i + 1;
         }
 
-        // for _i in 1..distance {
-        // }
-
         Ok(env)
     }
 
     pub fn assign_at(
-        &self,
+        &mut self,
         distance: usize,
-        // name: Token,
         name: String,
         value: RoxyType,
     ) -> Result<(), RoxyError> {
-        self.ancestor(distance)?.values.insert(name, value);
+        if distance == 0 {
+            self.values.insert(name, value);
+            return Ok(());
+        }
+
+        self.ancestor(distance)?
+            .borrow_mut()
+            .values
+            .insert(name, value);
         Ok(())
     }
 
-    pub fn assign(
-        &mut self,
-        name: String,
-        value: RoxyType,
-        globals: &mut Environment,
-    ) -> Result<(), RoxyError> {
-        if self.values.contains_key(&name) {
-            if self.enclosing == None {
-                globals.values.insert(name.clone(), value.clone());
-            }
-
-            self.values.insert(name.clone(), value);
+    pub fn assign(&mut self, name: String, value: RoxyType) -> Result<(), RoxyError> {
+        if let Some(slot) = self.values.get_mut(&name) {
+            *slot = value;
             return Ok(());
         }
 
-        if let Some(enclosing) = &mut self.enclosing {
-            return enclosing.assign(name, value, globals);
+        if let Some(enclosing) = &self.enclosing {
+            return enclosing.borrow_mut().assign(name, value);
         }
 
         return Err(RoxyError::EnvironmentError(
             EnvironmentError::UndefinedVariable(name),
         ));
     }
-
-    // pub fn update_at(
-    //     &mut self,
-    //     distance: usize,
-    //     name: String,
-    //     value: RoxyType,
-    // ) -> Result<(), RoxyError> {
-    //     self.ancestor(distance)?.values.insert(name, value);
-    //     Ok(())
-    // }
 }
@@ -1,8 +1,19 @@
-pub(crate) use crate::{RoxyType, Token};
+pub(crate) use crate::{span::Span, RoxyType, Token};
 use std::fmt::Formatter;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 //TODO: Try to shift this to something generated by macros
 
+// Every expression node gets an id unique for the lifetime of the process, so that the resolver
+// can tell apart two textually identical expressions living in different scopes
+pub type ExprId = usize;
+
+static NEXT_EXPR_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub fn next_expr_id() -> ExprId {
+    NEXT_EXPR_ID.fetch_add(1, Ordering::Relaxed)
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
     Assign(Assign),
@@ -10,8 +21,14 @@ pub enum Expr {
     Call(Call),
     Get(Get),
     Grouping(Grouping),
+    Index(Index),
+    IndexSet(IndexSet),
+    Interpolation(Interpolation),
+    Lambda(Lambda),
+    List(List),
     Literal(Literal),
     Logical(Logical),
+    Map(Map),
     Set(Set),
     Super(Super),
     This(This),
@@ -21,12 +38,16 @@ pub enum Expr {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assign {
+    pub id: ExprId,
+    pub span: Span,
     pub name: Token,
     pub value: Box<Expr>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Binary {
+    pub id: ExprId,
+    pub span: Span,
     pub left: Box<Expr>,
     pub operator: Token,
     pub right: Box<Expr>,
@@ -34,6 +55,8 @@ pub struct Binary {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Call {
+    pub id: ExprId,
+    pub span: Span,
     pub callee: Box<Expr>,
     pub paren: Token,
     pub arguments: Vec<Expr>,
@@ -41,29 +64,101 @@ pub struct Call {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Get {
+    pub id: ExprId,
+    pub span: Span,
     pub object: Box<Expr>,
     pub name: Token,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Grouping {
+    pub id: ExprId,
+    pub span: Span,
     pub expr: Box<Expr>,
 }
 
+// `object[index]`, the bracket is the closing one and is used for errors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index {
+    pub id: ExprId,
+    pub span: Span,
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexSet {
+    pub id: ExprId,
+    pub span: Span,
+    pub object: Box<Expr>,
+    pub bracket: Token,
+    pub index: Box<Expr>,
+    pub value: Box<Expr>,
+}
+
+// Anonymous function, either `fun (a, b) { ... }` or `(a, b) => a + b`. The body of the arrow
+// form is desugared to a single return statement
+#[derive(Debug, Clone)]
+pub struct Lambda {
+    pub id: ExprId,
+    pub span: Span,
+    pub keyword: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+}
+
+// Statements can't be compared, but no two lambdas share an id
+impl PartialEq for Lambda {
+    fn eq(&self, other: &Self) -> bool {
+        return self.id == other.id;
+    }
+}
+
+// `"a ${b} c"`, the pieces of text are string literals. Empty pieces are left out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interpolation {
+    pub id: ExprId,
+    pub span: Span,
+    pub parts: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct List {
+    pub id: ExprId,
+    pub span: Span,
+    pub elements: Vec<Expr>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Literal {
+    pub id: ExprId,
+    pub span: Span,
     pub value: RoxyType,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Logical {
+    pub id: ExprId,
+    pub span: Span,
     pub left: Box<Expr>,
     pub operator: Token,
     pub right: Box<Expr>,
 }
 
+// `{"a": 1}`, the brace is the closing one and is used for errors about keys
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    pub id: ExprId,
+    pub span: Span,
+    pub brace: Token,
+    pub entries: Vec<(Expr, Expr)>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Set {
+    pub id: ExprId,
+    pub span: Span,
     pub object: Box<Expr>,
     pub name: Token,
     pub value: Box<Expr>,
@@ -71,26 +166,82 @@ pub struct Set {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Super {
+    pub id: ExprId,
+    pub span: Span,
     pub keyword: Token,
     pub method: Token,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct This {
+    pub id: ExprId,
+    pub span: Span,
     pub keyword: Token,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Unary {
+    pub id: ExprId,
+    pub span: Span,
     pub operator: Token,
     pub right: Box<Expr>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Variable {
+    pub id: ExprId,
+    pub span: Span,
     pub name: Token,
 }
 
+impl Expr {
+    pub fn id(&self) -> ExprId {
+        match self {
+            Expr::Assign(assign) => assign.id,
+            Expr::Binary(binary) => binary.id,
+            Expr::Call(call) => call.id,
+            Expr::Get(get) => get.id,
+            Expr::Grouping(grouping) => grouping.id,
+            Expr::Index(index) => index.id,
+            Expr::IndexSet(index_set) => index_set.id,
+            Expr::Interpolation(interpolation) => interpolation.id,
+            Expr::Lambda(lambda) => lambda.id,
+            Expr::List(list) => list.id,
+            Expr::Literal(literal) => literal.id,
+            Expr::Logical(logical) => logical.id,
+            Expr::Map(map) => map.id,
+            Expr::Set(set) => set.id,
+            Expr::Super(suuper) => suuper.id,
+            Expr::This(this) => this.id,
+            Expr::Unary(unary) => unary.id,
+            Expr::Variable(var) => var.id,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Assign(assign) => assign.span,
+            Expr::Binary(binary) => binary.span,
+            Expr::Call(call) => call.span,
+            Expr::Get(get) => get.span,
+            Expr::Grouping(grouping) => grouping.span,
+            Expr::Index(index) => index.span,
+            Expr::IndexSet(index_set) => index_set.span,
+            Expr::Interpolation(interpolation) => interpolation.span,
+            Expr::Lambda(lambda) => lambda.span,
+            Expr::List(list) => list.span,
+            Expr::Literal(literal) => literal.span,
+            Expr::Logical(logical) => logical.span,
+            Expr::Map(map) => map.span,
+            Expr::Set(set) => set.span,
+            Expr::Super(suuper) => suuper.span,
+            Expr::This(this) => this.span,
+            Expr::Unary(unary) => unary.span,
+            Expr::Variable(var) => var.span,
+        }
+    }
+}
+
 impl std::fmt::Display for Expr {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -102,7 +253,10 @@ impl std::fmt::Display for Expr {
             ),
             Expr::Call(call) => {
                 write!(f, "{}(", call.callee)?;
-                for arg in &call.arguments {
+                for (i, arg) in call.arguments.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
                     write!(f, "{}", arg)?;
                 }
                 write!(f, ")")
@@ -113,6 +267,40 @@ impl std::fmt::Display for Expr {
             Expr::Grouping(grouping) => {
                 write!(f, "(group {})", grouping.expr)
             }
+            Expr::Index(index) => write!(f, "{}[{}]", index.object, index.index),
+            Expr::IndexSet(index_set) => write!(
+                f,
+                "{}[{}] = {}",
+                index_set.object, index_set.index, index_set.value
+            ),
+            Expr::Interpolation(interpolation) => {
+                write!(f, "\"")?;
+                for part in &interpolation.parts {
+                    match part {
+                        Expr::Literal(literal) => write!(f, "{}", literal.value)?,
+                        _ => write!(f, "${{{}}}", part)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Expr::Lambda(lambda) => {
+                let params: Vec<&str> = lambda
+                    .params
+                    .iter()
+                    .map(|param| param.lexeme.as_str())
+                    .collect();
+                write!(f, "(fun ({}) {{...}})", params.join(", "))
+            }
+            Expr::List(list) => {
+                write!(f, "[")?;
+                for (i, element) in list.elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
             Expr::Literal(literal) => {
                 write!(f, "{}", literal.value)
             }
@@ -120,9 +308,19 @@ impl std::fmt::Display for Expr {
                 write!(
                     f,
                     "({} {} {})",
-                    logical.right, logical.operator.lexeme, logical.left
+                    logical.left, logical.operator.lexeme, logical.right
                 )
             }
+            Expr::Map(map) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in map.entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
             Expr::Set(set) => write!(f, "{}.{} = {}", set.object, set.name.lexeme, set.value),
             Expr::Super(suuper) => {
                 write!(f, "{} {}()", suuper.keyword.lexeme, suuper.method.lexeme)
@@ -145,15 +343,135 @@ pub enum Stmt {
     VariableStmt(VariableStmt),
     While(While),
     Return(Return),
+    Break(Break),
+    Continue(Continue),
+    Import(Import),
+    Throw(Throw),
+    Try(Try),
+}
+
+impl Stmt {
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Block(block) => block.span,
+            Stmt::Class(class) => class.span,
+            Stmt::Expression(expr_stmt) => expr_stmt.span,
+            Stmt::Function(function) => function.span,
+            Stmt::If(if_stmt) => if_stmt.span,
+            Stmt::Print(print) => print.span,
+            Stmt::VariableStmt(var_stmt) => var_stmt.span,
+            Stmt::While(while_stmt) => while_stmt.span,
+            Stmt::Return(return_stmt) => return_stmt.span,
+            Stmt::Break(break_stmt) => break_stmt.span,
+            Stmt::Continue(continue_stmt) => continue_stmt.span,
+            Stmt::Import(import) => import.span,
+            Stmt::Throw(throw) => throw.span,
+            Stmt::Try(try_stmt) => try_stmt.span,
+        }
+    }
+
+    // Indented tree of the statement, one node per line. Expressions are printed through their
+    // Display impl
+    pub fn pretty_print(&self, indent: usize) -> String {
+        let pad = "  ".repeat(indent);
+        match self {
+            Stmt::Block(block) => {
+                format!(
+                    "{}Block\n{}",
+                    pad,
+                    pretty_print_all(&block.statements, indent + 1)
+                )
+            }
+            Stmt::Class(class) => {
+                let mut out = format!("{}Class {}", pad, class.name.lexeme);
+                if let Some(superclass) = &class.superclass {
+                    out.push_str(&format!(" < {}", superclass.name.lexeme));
+                }
+                out.push('\n');
+                for method in &class.methods {
+                    out.push_str(&method.pretty_print(indent + 1));
+                }
+                out
+            }
+            Stmt::Expression(expr_stmt) => format!("{}Expression {}\n", pad, expr_stmt.expression),
+            Stmt::Function(function) => function.pretty_print(indent),
+            Stmt::If(if_stmt) => {
+                let mut out = format!("{}If {}\n", pad, if_stmt.condition);
+                out.push_str(&if_stmt.then_branch.pretty_print(indent + 1));
+                if let Some(else_branch) = &if_stmt.else_branch {
+                    out.push_str(&format!("{}Else\n", pad));
+                    out.push_str(&else_branch.pretty_print(indent + 1));
+                }
+                out
+            }
+            Stmt::Print(print) => format!("{}Print {}\n", pad, print.expression),
+            Stmt::VariableStmt(var_stmt) => match &var_stmt.value {
+                Some(value) => format!("{}Var {} = {}\n", pad, var_stmt.name.lexeme, value),
+                None => format!("{}Var {}\n", pad, var_stmt.name.lexeme),
+            },
+            Stmt::While(while_stmt) => {
+                let mut out = format!("{}While {}\n", pad, while_stmt.condition);
+                out.push_str(&while_stmt.body.pretty_print(indent + 1));
+                if let Some(increment) = &while_stmt.increment {
+                    out.push_str(&format!("{}Increment {}\n", pad, increment));
+                }
+                out
+            }
+            Stmt::Return(return_stmt) => match &return_stmt.value {
+                Some(value) => format!("{}Return {}\n", pad, value),
+                None => format!("{}Return\n", pad),
+            },
+            Stmt::Break(_) => format!("{}Break\n", pad),
+            Stmt::Continue(_) => format!("{}Continue\n", pad),
+            Stmt::Import(import) => match &import.alias {
+                Some(alias) => {
+                    format!("{}Import {} as {}\n", pad, import.path.lexeme, alias.lexeme)
+                }
+                None => {
+                    let names: Vec<&str> = import
+                        .names
+                        .iter()
+                        .map(|name| name.lexeme.as_str())
+                        .collect();
+                    format!(
+                        "{}Import {} from {}\n",
+                        pad,
+                        names.join(", "),
+                        import.path.lexeme
+                    )
+                }
+            },
+            Stmt::Throw(throw) => format!("{}Throw {}\n", pad, throw.value),
+            Stmt::Try(try_stmt) => {
+                let mut out = format!("{}Try\n", pad);
+                out.push_str(&pretty_print_all(&try_stmt.body, indent + 1));
+                if let Some(catch) = &try_stmt.catch {
+                    out.push_str(&format!("{}Catch {}\n", pad, catch.name.lexeme));
+                    out.push_str(&pretty_print_all(&catch.body, indent + 1));
+                }
+                if let Some(finally) = &try_stmt.finally {
+                    out.push_str(&format!("{}Finally\n", pad));
+                    out.push_str(&pretty_print_all(finally, indent + 1));
+                }
+                out
+            }
+        }
+    }
+}
+
+pub fn pretty_print_all(stmts: &[Stmt], indent: usize) -> String {
+    return stmts.iter().map(|stmt| stmt.pretty_print(indent)).collect();
 }
 
 #[derive(Debug, Clone)]
 pub struct Block {
+    pub span: Span,
     pub statements: Vec<Stmt>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Class {
+    pub span: Span,
     pub name: Token,
     pub superclass: Option<Variable>,
     pub methods: Vec<Function>,
@@ -161,18 +479,38 @@ pub struct Class {
 
 #[derive(Debug, Clone)]
 pub struct ExpressionStmt {
+    pub span: Span,
     pub expression: Expr,
 }
 
 #[derive(Debug, Clone)]
 pub struct Function {
+    pub span: Span,
     pub name: Token,
     pub params: Vec<Token>,
     pub body: Vec<Stmt>,
 }
 
+impl Function {
+    fn pretty_print(&self, indent: usize) -> String {
+        let params: Vec<&str> = self
+            .params
+            .iter()
+            .map(|param| param.lexeme.as_str())
+            .collect();
+        format!(
+            "{}Function {}({})\n{}",
+            "  ".repeat(indent),
+            self.name.lexeme,
+            params.join(", "),
+            pretty_print_all(&self.body, indent + 1)
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct If {
+    pub span: Span,
     pub condition: Expr,
     pub then_branch: Box<Stmt>,
     pub else_branch: Option<Box<Stmt>>,
@@ -180,23 +518,74 @@ pub struct If {
 
 #[derive(Debug, Clone)]
 pub struct Print {
+    pub span: Span,
     pub expression: Expr,
 }
 
 #[derive(Debug, Clone)]
 pub struct Return {
+    pub span: Span,
     pub keyword: Token,
     pub value: Option<Expr>,
 }
 
 #[derive(Debug, Clone)]
 pub struct VariableStmt {
+    pub span: Span,
     pub name: Token,
     pub value: Option<Expr>,
 }
 
+// `increment` is only set for a desugared `for`, it runs after every iteration of the body, even
+// one cut short by `continue`
 #[derive(Debug, Clone)]
 pub struct While {
+    pub span: Span,
     pub condition: Expr,
     pub body: Box<Stmt>,
+    pub increment: Option<Box<Expr>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Break {
+    pub span: Span,
+    pub keyword: Token,
+}
+
+#[derive(Debug, Clone)]
+pub struct Continue {
+    pub span: Span,
+    pub keyword: Token,
+}
+
+// `import "path.rx" as alias;` sets `alias`, `from "path.rx" import a, b;` sets `names`
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub span: Span,
+    pub keyword: Token,
+    pub path: Token,
+    pub alias: Option<Token>,
+    pub names: Vec<Token>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Throw {
+    pub span: Span,
+    pub keyword: Token,
+    pub value: Expr,
+}
+
+#[derive(Debug, Clone)]
+pub struct Try {
+    pub span: Span,
+    pub keyword: Token,
+    pub body: Vec<Stmt>,
+    pub catch: Option<Catch>,
+    pub finally: Option<Vec<Stmt>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Catch {
+    pub name: Token,
+    pub body: Vec<Stmt>,
 }
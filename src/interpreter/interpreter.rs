@@ -1,18 +1,71 @@
-use crate::environment::Environment;
+use crate::environment::{EnvRef, Environment};
+use crate::interpreter::{list, map, module};
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::scanner::Scanner;
 use crate::tokens::TokenType;
-use crate::utils::errors::{InternalError, InterpreterError, RoxyError};
-use crate::RoxyClass;
+use crate::utils::errors::{
+    EnvironmentError, InternalError, InterpreterError, RoxyError, StackFrame,
+};
 use crate::{callable::Callable, expr::*, NativeFunction, RoxyFunction, RoxyType, TryConversion};
+use crate::{MapRef, RoxyClass, RoxyInstance, RoxyModule, Token};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+// Builtins which are easier to write in Lox than in Rust. `throw` fills in `kind` and `line`
+const PRELUDE: &str = "
+class Error {
+    init(message) {
+        this.message = message;
+        this.kind = nil;
+        this.line = nil;
+    }
+}
+";
+
+// How executing a statement ended. Anything but `Normal` skips the rest of the enclosing
+// statements until it reaches the loop or function call which handles it
 #[derive(Debug, Clone)]
+pub enum ControlFlow {
+    Normal,
+    Return(RoxyType),
+    Break,
+    Continue,
+}
+
+// A module is `Loading` while its top-level code runs, an import reaching it again in that time
+// is a cycle
+#[derive(Debug)]
+enum ModuleState {
+    Loading,
+    Loaded(Rc<RoxyModule>),
+}
+
+#[derive(Debug)]
 pub struct Interpreter {
-    pub globals: Box<Environment>,
+    // Natives, shared by every module. Each module's globals enclose them
+    pub builtins: EnvRef,
+    // Top-level environment of the module whose code is running
+    pub globals: EnvRef,
     //TODO: Make this private
-    pub environment: Box<Environment>,
-    locals: HashMap<String, usize>,
-    inside_function: bool,
+    pub environment: EnvRef,
+    locals: HashMap<ExprId, usize>,
+    // File of the module whose code is running, None for code not read from a file
+    current_file: Option<PathBuf>,
+    modules: HashMap<PathBuf, ModuleState>,
+    // Statements of the modules read so far. The resolver checks an import against the same
+    // statements the interpreter runs, even when the file changes in between
+    parsed_modules: HashMap<PathBuf, Vec<Stmt>>,
+    // Calls the error being raised has unwound through so far, innermost first
+    trace: Vec<StackFrame>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
@@ -20,28 +73,18 @@ impl Interpreter {
         match expr {
             Expr::Assign(assign_expr) => {
                 let value = self.evaluate(&assign_expr.value)?;
-                // let distance_opt = self.locals.get(&expr.to_string());
-                // if let Some(distance) = distance_opt {
-                //     self.environment.assign_at(
-                //         *distance,
-                //         assign_expr.name.lexeme.clone(),
-                //         value.clone(),
-                //     )?;
-                // } else {
-                //     self.globals
-                //         .assign(assign_expr.name.lexeme.clone(), value.clone())?;
-                // }
-
-                self.environment.assign(
-                    assign_expr.name.lexeme.clone(),
-                    value.clone(),
-                    &mut self.globals,
-                )?;
-
-                // if let None = self.environment.enclosing {
-                //     self.globals
-                //         .assign(assign_expr.name.lexeme.clone(), value.clone())?;
-                // }
+                if let Some(distance) = self.locals.get(&assign_expr.id) {
+                    self.environment.borrow_mut().assign_at(
+                        *distance,
+                        assign_expr.name.lexeme.clone(),
+                        value.clone(),
+                    )?;
+                } else {
+                    self.globals
+                        .borrow_mut()
+                        .assign(assign_expr.name.lexeme.clone(), value.clone())
+                        .map_err(|err| undefined_variable_at(err, &assign_expr.name))?;
+                }
 
                 return Ok(value);
             }
@@ -111,27 +154,18 @@ impl Interpreter {
                     arguments.push(self.evaluate(&argument.to_owned())?);
                 }
 
-                if arguments.len() != callee.arity(expr.paren.clone())? {
-                    return Err(RoxyError::InterpreterError(
-                        InterpreterError::ExpectednArgsGotmArgs(
-                            arguments.len(),
-                            callee.arity(expr.paren.clone())?,
-                            expr.paren.clone(),
-                        ),
-                    ));
-                }
-
-                return callee.call(
-                    self,
-                    arguments,
-                    expr.paren.clone(),
-                    self.environment.clone(),
-                );
+                return self.call_value(&callee, arguments, &expr.paren);
             }
             Expr::Get(get_expr) => {
                 let object = self.evaluate(&get_expr.object)?;
-                if let RoxyType::RoxyInstance(mut roxy_instance) = object {
-                    return roxy_instance.get(get_expr.name.clone());
+                match object {
+                    RoxyType::RoxyInstance(roxy_instance) => {
+                        return RoxyInstance::get(&roxy_instance, get_expr.name.clone());
+                    }
+                    RoxyType::List(roxy_list) => return list::method(&roxy_list, &get_expr.name),
+                    RoxyType::Map(roxy_map) => return map::method(&roxy_map, &get_expr.name),
+                    RoxyType::Module(module) => return module.get(get_expr.name.clone()),
+                    _ => {}
                 }
 
                 return Err(RoxyError::InterpreterError(
@@ -142,7 +176,85 @@ impl Interpreter {
                 ));
             }
             Expr::Grouping(expr) => self.evaluate(&expr.expr),
+            Expr::Index(index_expr) => {
+                let object = self.evaluate(&index_expr.object)?;
+                let index = self.evaluate(&index_expr.index)?;
+                match object {
+                    RoxyType::List(roxy_list) => {
+                        return list::get(&roxy_list, &index, &index_expr.bracket)
+                    }
+                    RoxyType::Map(roxy_map) => {
+                        return map::get(&roxy_map, &index, &index_expr.bracket)
+                    }
+                    _ => {
+                        return Err(RoxyError::InterpreterError(
+                            InterpreterError::OnlyListsAndMapsCanBeIndexed(
+                                index_expr.bracket.clone(),
+                            ),
+                        ))
+                    }
+                }
+            }
+            Expr::IndexSet(index_set) => {
+                let object = self.evaluate(&index_set.object)?;
+                let index = self.evaluate(&index_set.index)?;
+                let value = self.evaluate(&index_set.value)?;
+                match object {
+                    RoxyType::List(roxy_list) => {
+                        list::set(&roxy_list, &index, value.clone(), &index_set.bracket)?;
+                        return Ok(value);
+                    }
+                    RoxyType::Map(roxy_map) => {
+                        map::set(&roxy_map, &index, value.clone(), &index_set.bracket)?;
+                        return Ok(value);
+                    }
+                    _ => {
+                        return Err(RoxyError::InterpreterError(
+                            InterpreterError::OnlyListsAndMapsCanBeIndexed(
+                                index_set.bracket.clone(),
+                            ),
+                        ))
+                    }
+                }
+            }
+            Expr::Lambda(lambda) => Ok(RoxyType::RoxyFunction(Rc::new(RoxyFunction {
+                name: "anonymous".into(),
+                arity: lambda.params.len(),
+                params: lambda.params.clone(),
+                body: lambda.body.clone(),
+                closure: self.environment.clone(),
+                globals: self.globals.clone(),
+                file: self.current_file.clone(),
+                is_initializer: false,
+            }))),
+            Expr::Interpolation(interpolation) => {
+                // Values read the same as when they are printed
+                let mut text = String::new();
+                for part in &interpolation.parts {
+                    text.push_str(&self.evaluate(part)?.to_string());
+                }
+
+                return Ok(RoxyType::String(text));
+            }
+            Expr::List(list_expr) => {
+                let mut elements = vec![];
+                for element in &list_expr.elements {
+                    elements.push(self.evaluate(element)?);
+                }
+
+                return Ok(RoxyType::from(elements));
+            }
             Expr::Literal(expr) => Ok(expr.value.clone()),
+            Expr::Map(map_expr) => {
+                let roxy_map = MapRef::default();
+                for (key, value) in &map_expr.entries {
+                    let key = self.evaluate(key)?;
+                    let value = self.evaluate(value)?;
+                    map::set(&roxy_map, &key, value, &map_expr.brace)?;
+                }
+
+                return Ok(RoxyType::Map(roxy_map));
+            }
             Expr::Logical(expr) => {
                 let left = self.evaluate(&expr.left)?;
                 if expr.operator.token_type == TokenType::Or {
@@ -155,47 +267,16 @@ impl Interpreter {
                     }
                 }
 
-                return Ok(self.evaluate(&expr.right)?);
+                return self.evaluate(&expr.right);
             }
             Expr::Set(set_expr) => {
                 let object = self.evaluate(&set_expr.object)?;
 
-                if let RoxyType::RoxyInstance(mut roxy_instance) = object {
+                if let RoxyType::RoxyInstance(roxy_instance) = object {
                     let value = self.evaluate(&set_expr.value)?;
-                    roxy_instance.set(set_expr.name.clone(), value.clone());
-                    for (key, value) in self.environment.clone().values {
-                        if let RoxyType::RoxyInstance(instance) = value {
-                            if instance.klass.name == roxy_instance.clone().klass.name {
-                                self.environment.assign(
-                                    key.to_owned(),
-                                    RoxyType::RoxyInstance(roxy_instance.clone()),
-                                    &mut self.globals,
-                                )?;
-                            }
-                        }
-                    }
-
-                    self.environment.assign(
-                        roxy_instance.klass.name.clone(),
-                        RoxyType::RoxyInstance(roxy_instance.clone()),
-                        &mut self.globals,
-                    )?;
-                    // if let None = self.environment.enclosing {
-                    //     for (key, value) in self.globals.clone().values {
-                    //         if let RoxyType::RoxyInstance(instance) = value {
-                    //             if instance.klass.name == roxy_instance.clone().klass.name {
-                    //                 self.globals.assign(
-                    //                     key.to_owned(),
-                    //                     RoxyType::RoxyInstance(roxy_instance.clone()),
-                    //                 )?;
-                    //             }
-                    //         }
-                    //     }
-                    //     self.globals.assign(
-                    //         roxy_instance.klass.name.clone(),
-                    //         RoxyType::RoxyInstance(roxy_instance),
-                    //     )?;
-                    // }
+                    roxy_instance
+                        .borrow_mut()
+                        .set(set_expr.name.clone(), value.clone());
 
                     return Ok(value);
                 }
@@ -208,15 +289,25 @@ impl Interpreter {
                 ));
             }
             Expr::Super(super_expr) => {
-                let distance = self.locals[&Expr::Super(super_expr.to_owned()).to_string()];
-                let superclass = self.environment.get_at(distance, "super".into())?;
-                let object = self.environment.get_at(distance - 1, "this".into())?;
+                let distance = match self.locals.get(&super_expr.id) {
+                    Some(distance) => *distance,
+                    None => {
+                        return Err(RoxyError::EnvironmentError(
+                            EnvironmentError::UndefinedVariable(super_expr.keyword.lexeme.clone()),
+                        ))
+                    }
+                };
+                let superclass = self.environment.borrow().get_at(distance, "super".into())?;
+                let object = self
+                    .environment
+                    .borrow()
+                    .get_at(distance - 1, "this".into())?;
 
                 if let RoxyType::RoxyClass(superclass) = superclass {
                     if let Some(method) = superclass.find_method(super_expr.method.lexeme.clone()) {
                         match object {
                             RoxyType::RoxyInstance(instance) => {
-                                return Ok(RoxyType::RoxyFunction(method.bind(&instance)));
+                                return Ok(RoxyType::RoxyFunction(Rc::new(method.bind(&instance))));
                             }
                             _ => unreachable!(),
                         }
@@ -232,8 +323,7 @@ impl Interpreter {
                 ));
             }
             Expr::This(this_expr) => {
-                return self.environment.get(this_expr.keyword.lexeme.clone());
-                // return self.look_up_variable(this_expr.keyword.clone(), expr.clone());
+                return self.look_up_variable(this_expr.keyword.clone(), this_expr.id);
             }
             Expr::Unary(expr) => {
                 let right = self.evaluate(&expr.right)?;
@@ -250,60 +340,61 @@ impl Interpreter {
                 }
             }
             Expr::Variable(variable) => {
-                let res = self.look_up_variable(variable.name.clone(), expr.to_owned());
-                return res;
-                // return self.environment.get(variable.name.clone());
+                return self.look_up_variable(variable.name.clone(), variable.id);
             }
         }
     }
 
-    fn look_up_variable(&mut self, name: Token, variable: Expr) -> Result<RoxyType, RoxyError> {
-        let distance_opt = self.locals.get(&variable.to_string());
+    fn look_up_variable(&mut self, name: Token, id: ExprId) -> Result<RoxyType, RoxyError> {
+        let distance_opt = self.locals.get(&id);
 
-        if let Some(distance) = distance_opt {
-            let res = self.environment.get_at(*distance, name.lexeme)?;
-            return Ok(res);
-        } else {
-            let res = self.globals.get(name.lexeme);
-            return res;
-        }
+        let res = match distance_opt {
+            Some(distance) => self
+                .environment
+                .borrow()
+                .get_at(*distance, name.lexeme.clone()),
+            None => self.globals.borrow().get(name.lexeme.clone()),
+        };
+
+        return res.map_err(|err| undefined_variable_at(err, &name));
     }
 
     pub fn execute_block(
         &mut self,
         stmts: Vec<Stmt>,
-        env: Box<Environment>,
-    ) -> Result<Option<RoxyType>, RoxyError> {
-        self.environment = env;
-
-        for stmt in stmts.clone() {
-            //TODO: Remove clone from here
-            if let Some(value) = self.interpret(stmt.clone())? {
-                self.environment = self.environment.clone().enclosing.unwrap();
-                return Ok(Some(value));
+        env: EnvRef,
+    ) -> Result<ControlFlow, RoxyError> {
+        let previous = std::mem::replace(&mut self.environment, env);
+
+        let mut result = Ok(ControlFlow::Normal);
+        for stmt in stmts {
+            match self.interpret(stmt) {
+                Ok(ControlFlow::Normal) => {}
+                res => {
+                    result = res;
+                    break;
+                }
             }
         }
 
-        //TODO: Think how to remove this unwrap
-        self.environment = self.environment.clone().enclosing.unwrap();
+        // Restore the caller's environment on every path, errors included
+        self.environment = previous;
 
-        Ok(None)
+        result
     }
 
-    pub fn interpret(&mut self, stmt: Stmt) -> Result<Option<RoxyType>, RoxyError> {
+    pub fn interpret(&mut self, stmt: Stmt) -> Result<ControlFlow, RoxyError> {
         match stmt {
             Stmt::Block(block) => {
-                let block_env = Box::new(Environment::new_with_enclosing(self.environment.clone()));
+                let block_env = Environment::new_ref(Some(self.environment.clone()));
                 return self.execute_block(block.statements, block_env);
             }
             Stmt::Class(class_stmt) => {
                 let mut superclass_opt = None;
 
-                self.environment.define(
-                    class_stmt.name.lexeme.clone(),
-                    RoxyType::NULL,
-                    Some(&mut self.globals),
-                );
+                self.environment
+                    .borrow_mut()
+                    .define(class_stmt.name.lexeme.clone(), RoxyType::NULL);
 
                 if let Some(superclass) = &class_stmt.superclass {
                     let roxy_type = self.evaluate(&Expr::Variable(superclass.clone()))?;
@@ -311,15 +402,12 @@ impl Interpreter {
                         RoxyType::RoxyClass(class) => {
                             let mut superclass_env =
                                 Environment::new_with_enclosing(self.environment.clone());
-                            superclass_env.define(
-                                "super".into(),
-                                RoxyType::RoxyClass(class.clone()),
-                                Some(&mut self.globals),
-                            );
+                            superclass_env
+                                .define("super".into(), RoxyType::RoxyClass(class.clone()));
 
-                            self.environment = Box::new(superclass_env);
+                            self.environment = Rc::new(superclass_env.into());
 
-                            superclass_opt = Some(Box::new(class));
+                            superclass_opt = Some(class);
                         }
                         _ => {
                             return Err(RoxyError::InterpreterError(
@@ -329,11 +417,6 @@ impl Interpreter {
                     }
                 }
 
-                // if let None = self.environment.enclosing {
-                //     self.globals
-                //         .define(class_stmt.name.lexeme.clone(), RoxyType::NULL);
-                // }
-
                 let mut methods = HashMap::new();
                 for method in class_stmt.methods {
                     methods.insert(
@@ -343,72 +426,48 @@ impl Interpreter {
                             arity: method.params.len(),
                             params: method.params,
                             body: method.body,
-                            closure: Some(self.environment.clone()),
-                            is_method: true,
-                            is_initializer: (method.name.lexeme.eq("into")),
+                            closure: self.environment.clone(),
+                            globals: self.globals.clone(),
+                            file: self.current_file.clone(),
+                            is_initializer: (method.name.lexeme.eq("init")),
                         },
                     );
                 }
 
-                let klass = RoxyType::RoxyClass(RoxyClass {
+                let klass = RoxyType::RoxyClass(Rc::new(RoxyClass {
                     name: class_stmt.name.lexeme.clone(),
                     methods,
                     superclass: superclass_opt,
-                });
+                }));
 
                 if class_stmt.superclass.is_some() {
-                    self.environment = self.environment.enclosing.clone().unwrap();
+                    let enclosing = self.environment.borrow().enclosing.clone();
+                    if let Some(enclosing) = enclosing {
+                        self.environment = enclosing;
+                    }
                 }
 
-                self.environment.assign(
-                    class_stmt.name.lexeme.clone(),
-                    klass.clone(),
-                    &mut self.globals,
-                )?;
-
-                // if let None = self.environment.enclosing {
-                //     self.globals.assign(class_stmt.name.lexeme, klass)?;
-                // }
+                self.environment
+                    .borrow_mut()
+                    .assign(class_stmt.name.lexeme.clone(), klass)?;
             }
             Stmt::Expression(expr_stmt) => {
                 self.evaluate(&expr_stmt.expression)?;
             }
-            // TODO: Implement anonymous functions
             Stmt::Function(function) => {
-                let closure;
-                if self.inside_function {
-                    closure = Some(self.environment.clone());
-                } else {
-                    closure = None;
-                }
-
-                self.environment.define(
+                self.environment.borrow_mut().define(
                     function.name.lexeme.clone(),
-                    RoxyType::RoxyFunction(RoxyFunction {
+                    RoxyType::RoxyFunction(Rc::new(RoxyFunction {
                         name: function.name.lexeme.clone(),
                         arity: function.params.len(),
                         params: function.params.clone(),
                         body: function.body.clone(),
-                        // closure: Some(self.environment.clone()),
-                        closure,
-                        is_method: false,
+                        closure: self.environment.clone(),
+                        globals: self.globals.clone(),
+                        file: self.current_file.clone(),
                         is_initializer: false,
-                    }),
-                    Some(&mut self.globals),
+                    })),
                 );
-                // if let None = self.environment.enclosing {
-                //     self.globals.define(
-                //         function.name.lexeme.clone(),
-                //         RoxyType::RoxyFunction(RoxyFunction {
-                //             name: function.name.lexeme.clone(),
-                //             arity: function.params.len(),
-                //             params: function.params,
-                //             body: function.body,
-                //             closure: self.environment.clone(),
-                //             is_method: false,
-                //         }),
-                //     );
-                // }
             }
             Stmt::If(if_stmt) => {
                 let value = self.evaluate(&if_stmt.condition)?;
@@ -421,18 +480,8 @@ impl Interpreter {
                 }
             }
             Stmt::Print(print_stmt) => {
-                //TODO: Implement print and println properly
                 let roxy_type = self.evaluate(&print_stmt.expression)?;
-                println!("{:?}", roxy_type.to_string());
-                //NOTE: In testing
-                // match roxy_type {
-                //     RoxyType::String(type_string) => println!("{:?}", type_string),
-                //     RoxyType::Number(number) => println!("{:?}", number),
-                //     RoxyType::NULL => println!("NULL"),
-                //     RoxyType::Boolean(type_bool) => println!("{:?}", type_bool),
-                //     RoxyType::Object => println!("Object"),
-                //     RoxyType::RoxyFunction => println!("RoxyFunction"),
-                // };
+                println!("{}", roxy_type);
             }
             Stmt::VariableStmt(var_stmt) => {
                 let mut value: RoxyType = RoxyType::NULL;
@@ -440,98 +489,378 @@ impl Interpreter {
                     value = self.evaluate(&expr)?;
                 }
 
-                self.environment.define(
-                    var_stmt.name.lexeme.clone(),
-                    value.clone(),
-                    Some(&mut self.globals),
-                );
-
-                // if let None = self.environment.enclosing {
-                //     self.globals.define(var_stmt.name.lexeme, value);
-                // }
-            }
-            Stmt::While(while_stmt) => {
-                let mut condition = self.evaluate(&while_stmt.condition)?;
-                //TODO: Implement break(and continue) keywords
-                let mut i = 0;
-                while self.is_truthy(&condition) {
-                    if i > 10 {
-                        break;
-                    }
-                    if let Some(value) = self.interpret(*(while_stmt.body.clone()))? {
-                        return Ok(Some(value));
-                    }
+                self.environment
+                    .borrow_mut()
+                    .define(var_stmt.name.lexeme.clone(), value);
+            }
+            Stmt::While(while_stmt) => loop {
+                let condition = self.evaluate(&while_stmt.condition)?;
+                if !self.is_truthy(&condition) {
+                    break;
+                }
 
-                    condition = self.evaluate(&while_stmt.condition)?;
+                match self.interpret(*(while_stmt.body.clone()))? {
+                    ControlFlow::Return(value) => return Ok(ControlFlow::Return(value)),
+                    ControlFlow::Break => break,
+                    ControlFlow::Normal | ControlFlow::Continue => {}
+                }
 
-                    i = i + 1;
+                if let Some(increment) = &while_stmt.increment {
+                    self.evaluate(increment)?;
                 }
-            }
+            },
             Stmt::Return(return_stmt) => {
                 if let Some(expr) = return_stmt.value {
-                    //TODO: HOW TO RETURN THIS value?
                     let value = self.evaluate(&expr)?;
-                    return Ok(Some(value));
+                    return Ok(ControlFlow::Return(value));
+                }
+
+                return Ok(ControlFlow::Return(RoxyType::NULL));
+            }
+            Stmt::Break(_) => return Ok(ControlFlow::Break),
+            Stmt::Continue(_) => return Ok(ControlFlow::Continue),
+            Stmt::Throw(throw) => {
+                let value = self.evaluate(&throw.value)?;
+                if let RoxyType::RoxyInstance(instance) = &value {
+                    if self.is_error(&instance.borrow().klass) {
+                        let mut instance = instance.borrow_mut();
+                        let kind = instance.klass.name.clone();
+                        let line = RoxyType::Number(throw.keyword.line as f64);
+                        for (field, value) in [("kind", kind.into()), ("line", line)] {
+                            let field = instance
+                                .fields
+                                .entry(field.to_string())
+                                .or_insert(RoxyType::NULL);
+                            if *field == RoxyType::NULL {
+                                *field = value;
+                            }
+                        }
+                    }
                 }
 
-                return Ok(Some(RoxyType::NULL));
+                return Err(RoxyError::InterpreterError(InterpreterError::Thrown(
+                    value,
+                    throw.keyword,
+                )));
+            }
+            Stmt::Try(try_stmt) => {
+                let body_env = Environment::new_ref(Some(self.environment.clone()));
+                let result = self.execute_block(try_stmt.body, body_env);
+
+                let result = match (result, try_stmt.catch) {
+                    (Err(err), Some(catch)) if err.is_runtime() => {
+                        self.trace.clear();
+                        let catch_env = Environment::new_ref(Some(self.environment.clone()));
+                        catch_env
+                            .borrow_mut()
+                            .define(catch.name.lexeme, self.error_value(err));
+                        self.execute_block(catch.body, catch_env)
+                    }
+                    (result, _) => result,
+                };
+
+                // Runs however the rest ended. Leaving `finally` early replaces what was pending,
+                // otherwise the pending return, error or jump carries on
+                if let Some(finally) = try_stmt.finally {
+                    let trace = std::mem::take(&mut self.trace);
+                    let finally_env = Environment::new_ref(Some(self.environment.clone()));
+                    match self.execute_block(finally, finally_env)? {
+                        ControlFlow::Normal => self.trace = trace,
+                        flow => return Ok(flow),
+                    }
+                }
+
+                return result;
+            }
+            Stmt::Import(import) => {
+                let module = self.import_module(&import.path)?;
+                if let Some(alias) = import.alias {
+                    self.environment
+                        .borrow_mut()
+                        .define(alias.lexeme, RoxyType::Module(module));
+                    return Ok(ControlFlow::Normal);
+                }
+
+                for name in import.names {
+                    let value = module.get(name.clone())?;
+                    self.environment.borrow_mut().define(name.lexeme, value);
+                }
             }
         }
 
-        Ok(None)
+        Ok(ControlFlow::Normal)
     }
 
-    fn is_truthy(&self, value: &RoxyType) -> bool {
-        match value {
-            RoxyType::Boolean(val) => val.clone(),
-            RoxyType::String(_) => true,
-            _ => false,
+    // Calls a function, method, class or native after checking the number of arguments
+    pub fn call_value(
+        &mut self,
+        callee: &RoxyType,
+        arguments: Vec<RoxyType>,
+        token: &Token,
+    ) -> Result<RoxyType, RoxyError> {
+        let arity = callee.arity(token.clone())?;
+        if arguments.len() != arity {
+            return Err(RoxyError::InterpreterError(
+                InterpreterError::ExpectednArgsGotmArgs(arity, arguments.len(), token.clone()),
+            ));
         }
+
+        return callee.call(self, arguments, token.clone());
+    }
+
+    // What a `catch` binds. Thrown values are passed on as they are, errors raised by the
+    // interpreter become `Error` instances
+    fn error_value(&self, err: RoxyError) -> RoxyType {
+        let kind = match &err {
+            RoxyError::InterpreterError(InterpreterError::Thrown(value, _)) => {
+                return value.clone()
+            }
+            RoxyError::InterpreterError(err) => err.name(),
+            _ => err.kind(),
+        };
+        let line = match err.span() {
+            Some(span) => RoxyType::Number(span.line as f64),
+            None => RoxyType::NULL,
+        };
+
+        let instance = match self.builtins.borrow().get("Error".into()) {
+            Ok(RoxyType::RoxyClass(klass)) => RoxyInstance::new(klass),
+            _ => unreachable!("the prelude defines `Error`"),
+        };
+        {
+            let mut fields = instance.borrow_mut();
+            fields.fields.insert("message".into(), err.message().into());
+            fields.fields.insert("kind".into(), kind.into());
+            fields.fields.insert("line".into(), line);
+        }
+
+        return RoxyType::RoxyInstance(instance);
+    }
+
+    // `Error` or one of its subclasses
+    fn is_error(&self, klass: &Rc<RoxyClass>) -> bool {
+        let error = match self.builtins.borrow().get("Error".into()) {
+            Ok(RoxyType::RoxyClass(error)) => error,
+            _ => return false,
+        };
+
+        let mut klass = Some(klass.clone());
+        while let Some(current) = klass {
+            if Rc::ptr_eq(&current, &error) {
+                return true;
+            }
+            klass = current.superclass.clone();
+        }
+
+        return false;
+    }
+
+    // Called as an error leaves a function, see `take_trace`
+    pub fn push_frame(&mut self, function: &RoxyFunction, line: usize) {
+        self.trace.push(StackFrame {
+            function: function.name.clone(),
+            file: function.file.clone(),
+            line,
+        });
+    }
+
+    // Hands over the trace of the error that just reached the top, leaving it empty for the next
+    pub fn take_trace(&mut self) -> Vec<StackFrame> {
+        return std::mem::take(&mut self.trace);
+    }
+
+    fn is_truthy(&self, value: &RoxyType) -> bool {
+        value.is_truthy()
     }
 
     pub fn new() -> Self {
         //NOTE: Look into global env too while resolving the function calls
-        let mut global_env = Environment::new();
-        global_env.define(
-            String::from("clock"),
+        let builtins = Environment::new_ref(None);
+        let globals = Environment::new_ref(Some(builtins.clone()));
+        let mut interpreter = Self {
+            environment: globals.clone(),
+            builtins,
+            globals,
+            locals: HashMap::new(),
+            current_file: None,
+            modules: HashMap::new(),
+            parsed_modules: HashMap::new(),
+            trace: vec![],
+        };
+
+        interpreter.register_native("clock", 0, |_, _, token| {
+            let start = SystemTime::now();
+            match start.duration_since(UNIX_EPOCH) {
+                Ok(since_the_epoch) => Ok(RoxyType::Number(since_the_epoch.as_millis() as f64)),
+                Err(_) => Err(RoxyError::InternalError(
+                    InternalError::TimeConversionError(token),
+                )),
+            }
+        });
+        interpreter.run_prelude();
+
+        return interpreter;
+    }
+
+    fn run_prelude(&mut self) {
+        let mut scanner = Scanner::new(PRELUDE.to_string());
+        let tokens = scanner.scan_tokens().expect("the prelude scans");
+        let stmts = Parser::new(tokens.clone())
+            .parse()
+            .expect("the prelude parses");
+
+        let previous_globals = std::mem::replace(&mut self.globals, self.builtins.clone());
+        let previous_env = std::mem::replace(&mut self.environment, self.builtins.clone());
+        self.run_module(stmts).expect("the prelude runs");
+        self.globals = previous_globals;
+        self.environment = previous_env;
+    }
+
+    // Exposes a Rust closure to scripts as a global function. The interpreter checks the number
+    // of arguments against `arity` before the closure is called
+    pub fn register_native<F>(&mut self, name: &str, arity: usize, callable: F)
+    where
+        F: Fn(&mut Interpreter, Vec<RoxyType>, Token) -> Result<RoxyType, RoxyError> + 'static,
+    {
+        self.builtins.borrow_mut().define(
+            name.to_string(),
             RoxyType::NativeFunction(NativeFunction {
-                name: String::from("check"),
+                name: name.to_string(),
+                arity,
                 params: vec![],
-                arity: 0,
-                callable: |_: &RoxyType,
-                           _: &mut Interpreter,
-                           _: Vec<RoxyType>,
-                           token: Token|
-                 -> Result<RoxyType, RoxyError> {
-                    let start = SystemTime::now();
-                    match start.duration_since(UNIX_EPOCH) {
-                        Ok(since_the_epoch) => {
-                            Ok(RoxyType::Number(since_the_epoch.as_millis() as f64))
-                        }
-                        Err(_) => Err(RoxyError::InternalError(
-                            InternalError::TimeConversionError(token),
-                        )),
-                    }
-                },
+                callable: Rc::new(callable),
             }),
-            None,
         );
+    }
 
-        return Self {
-            environment: Box::new(global_env.clone()),
-            globals: Box::new(global_env),
-            locals: HashMap::new(),
-            inside_function: false,
-        };
+    pub fn resolve(&mut self, id: ExprId, depth: usize) {
+        self.locals.insert(id, depth);
+    }
+
+    // File the code about to run was read from, imports in it are relative to this file
+    pub fn set_current_file(&mut self, path: &Path) {
+        self.current_file = Some(path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+    }
+
+    pub fn current_file(&self) -> Option<&Path> {
+        return self.current_file.as_deref();
+    }
+
+    // Canonical path of the module an import names, so that every way of naming a file is
+    // cached under the same key
+    pub fn module_path(&self, path_token: &Token) -> Result<PathBuf, RoxyError> {
+        let path = module::resolve_path(self.current_file(), &path_token.literal.to_string());
+        match path.canonicalize() {
+            Ok(path) => return Ok(path),
+            Err(_) => {
+                return Err(RoxyError::InterpreterError(
+                    InterpreterError::ModuleNotFound(
+                        path_token.literal.to_string(),
+                        path_token.clone(),
+                    ),
+                ))
+            }
+        }
+    }
+
+    // Each file is read and parsed once, a failed import forgets it so the next one reads it again
+    pub fn parse_module(
+        &mut self,
+        path: &Path,
+        path_token: &Token,
+    ) -> Result<Vec<Stmt>, RoxyError> {
+        if let Some(stmts) = self.parsed_modules.get(path) {
+            return Ok(stmts.clone());
+        }
+
+        let stmts = module::parse(path, path_token)?;
+        self.parsed_modules
+            .insert(path.to_path_buf(), stmts.clone());
+        return Ok(stmts);
+    }
+
+    // Runs the module the first time it is imported, later imports get the cached namespace
+    fn import_module(&mut self, path_token: &Token) -> Result<Rc<RoxyModule>, RoxyError> {
+        let path = self.module_path(path_token)?;
+
+        match self.modules.get(&path) {
+            Some(ModuleState::Loaded(module)) => return Ok(module.clone()),
+            Some(ModuleState::Loading) => {
+                return Err(RoxyError::InterpreterError(InterpreterError::ImportCycle(
+                    module::display_name(&path),
+                    path_token.clone(),
+                )))
+            }
+            None => {}
+        }
+
+        let stmts = self.parse_module(&path, path_token)?;
+        self.modules.insert(path.clone(), ModuleState::Loading);
+
+        let env = Environment::new_ref(Some(self.builtins.clone()));
+        let previous_file = self.current_file.replace(path.clone());
+        let previous_globals = std::mem::replace(&mut self.globals, env.clone());
+        let previous_env = std::mem::replace(&mut self.environment, env.clone());
+
+        let result = self.run_module(stmts);
+
+        self.current_file = previous_file;
+        self.globals = previous_globals;
+        self.environment = previous_env;
+
+        let name = module::display_name(&path);
+        if let Err(err) = result {
+            // Lines in the trace are in the other file, the error names the line instead
+            self.trace.clear();
+            self.modules.remove(&path);
+            self.parsed_modules.remove(&path);
+            return Err(match err {
+                // Grows into `a.rx -> b.rx -> a.rx` on the way back to the first import
+                RoxyError::InterpreterError(InterpreterError::ImportCycle(cycle, _)) => {
+                    RoxyError::InterpreterError(InterpreterError::ImportCycle(
+                        format!("{} -> {}", name, cycle),
+                        path_token.clone(),
+                    ))
+                }
+                _ => RoxyError::InterpreterError(InterpreterError::ErrorInModule(
+                    name,
+                    module::describe(&err),
+                    path_token.clone(),
+                )),
+            });
+        }
+
+        let module = Rc::new(RoxyModule {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or(name),
+            path: path.clone(),
+            env,
+        });
+        self.modules
+            .insert(path, ModuleState::Loaded(module.clone()));
+
+        return Ok(module);
     }
 
-    pub fn resolve(&mut self, expr: Expr, depth: usize) {
-        // let env_string =
-        self.locals.insert(expr.to_string(), depth);
+    fn run_module(&mut self, stmts: Vec<Stmt>) -> Result<(), RoxyError> {
+        Resolver::new(self).resolve(stmts.clone())?;
+        for stmt in stmts {
+            self.interpret(stmt)?;
+        }
+
+        return Ok(());
     }
+}
 
-    pub fn set_is_calling(&mut self, inside_function: bool) {
-        self.inside_function = inside_function;
+// Environments only know about names, so attach the token to let the error point into the source
+fn undefined_variable_at(err: RoxyError, name: &Token) -> RoxyError {
+    match err {
+        RoxyError::EnvironmentError(EnvironmentError::UndefinedVariable(_)) => {
+            RoxyError::InterpreterError(InterpreterError::UndefinedVariable(name.clone()))
+        }
+        _ => err,
     }
 }
 
@@ -0,0 +1,181 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::{
+    utils::errors::{InterpreterError, RoxyError},
+    ListRef, NativeFn, NativeFunction, RoxyType, Token,
+};
+
+// Turns an index value into a position in a list of length `len`. `end` allows the position just
+// past the last element, which is where `insert` appends and `slice` stops
+pub fn position(
+    index: &RoxyType,
+    len: usize,
+    end: bool,
+    token: &Token,
+) -> Result<usize, RoxyError> {
+    let index = match index {
+        RoxyType::Number(index) if index.fract() == 0.0 => *index,
+        _ => {
+            return Err(RoxyError::InterpreterError(
+                InterpreterError::IndexMustBeAnInteger(token.clone()),
+            ))
+        }
+    };
+
+    let limit = if end { len + 1 } else { len };
+    if index < 0.0 || index >= limit as f64 {
+        return Err(RoxyError::InterpreterError(
+            InterpreterError::IndexOutOfBounds(index, len, token.clone()),
+        ));
+    }
+
+    return Ok(index as usize);
+}
+
+pub fn get(list: &ListRef, index: &RoxyType, token: &Token) -> Result<RoxyType, RoxyError> {
+    let list = list.borrow();
+    let i = position(index, list.len(), false, token)?;
+    return Ok(list[i].clone());
+}
+
+pub fn set(
+    list: &ListRef,
+    index: &RoxyType,
+    value: RoxyType,
+    token: &Token,
+) -> Result<(), RoxyError> {
+    let mut list = list.borrow_mut();
+    let i = position(index, list.len(), false, token)?;
+    list[i] = value;
+    return Ok(());
+}
+
+// Methods are natives bound to the list they were looked up on, `xs.push` can be stored and
+// called later like any other function
+pub fn method(list: &ListRef, name: &Token) -> Result<RoxyType, RoxyError> {
+    let list = list.clone();
+    let (arity, callable): (usize, NativeFn) = match name.lexeme.as_str() {
+        "len" => (
+            0,
+            Rc::new(move |_, _, _| Ok(RoxyType::Number(list.borrow().len() as f64))),
+        ),
+        "push" => (
+            1,
+            Rc::new(move |_, mut args: Vec<RoxyType>, _| {
+                list.borrow_mut().push(args.remove(0));
+                Ok(RoxyType::NULL)
+            }),
+        ),
+        "pop" => (
+            0,
+            Rc::new(move |_, _, token| match list.borrow_mut().pop() {
+                Some(value) => Ok(value),
+                None => Err(RoxyError::InterpreterError(
+                    InterpreterError::PopFromEmptyList(token),
+                )),
+            }),
+        ),
+        "insert" => (
+            2,
+            Rc::new(move |_, mut args: Vec<RoxyType>, token| {
+                let mut list = list.borrow_mut();
+                let i = position(&args[0], list.len(), true, &token)?;
+                list.insert(i, args.remove(1));
+                Ok(RoxyType::NULL)
+            }),
+        ),
+        "remove" => (
+            1,
+            Rc::new(move |_, args: Vec<RoxyType>, token| {
+                let mut list = list.borrow_mut();
+                let i = position(&args[0], list.len(), false, &token)?;
+                Ok(list.remove(i))
+            }),
+        ),
+        "slice" => (
+            2,
+            Rc::new(move |_, args: Vec<RoxyType>, token| {
+                let list = list.borrow();
+                let start = position(&args[0], list.len(), true, &token)?;
+                let end = position(&args[1], list.len(), true, &token)?;
+                let slice = if start < end {
+                    list[start..end].to_vec()
+                } else {
+                    vec![]
+                };
+                Ok(RoxyType::from(slice))
+            }),
+        ),
+        "map" => (
+            1,
+            Rc::new(move |interpreter, args: Vec<RoxyType>, token| {
+                // The callback is free to modify the list, so iterate over a copy
+                let elements = list.borrow().clone();
+                let mut mapped = vec![];
+                for element in elements {
+                    mapped.push(interpreter.call_value(&args[0], vec![element], &token)?);
+                }
+                Ok(RoxyType::from(mapped))
+            }),
+        ),
+        "filter" => (
+            1,
+            Rc::new(move |interpreter, args: Vec<RoxyType>, token| {
+                let elements = list.borrow().clone();
+                let mut kept = vec![];
+                for element in elements {
+                    let keep = interpreter.call_value(&args[0], vec![element.clone()], &token)?;
+                    if keep.is_truthy() {
+                        kept.push(element);
+                    }
+                }
+                Ok(RoxyType::from(kept))
+            }),
+        ),
+        "reduce" => (
+            2,
+            Rc::new(move |interpreter, mut args: Vec<RoxyType>, token| {
+                let elements = list.borrow().clone();
+                let mut accumulator = args.remove(1);
+                for element in elements {
+                    accumulator =
+                        interpreter.call_value(&args[0], vec![accumulator, element], &token)?;
+                }
+                Ok(accumulator)
+            }),
+        ),
+        "sort" => (
+            0,
+            Rc::new(move |_, _, token| {
+                let mut list = list.borrow_mut();
+                let sortable = list.iter().all(|v| matches!(v, RoxyType::Number(_)))
+                    || list.iter().all(|v| matches!(v, RoxyType::String(_)));
+                if !sortable {
+                    return Err(RoxyError::InterpreterError(InterpreterError::CantSortList(
+                        token,
+                    )));
+                }
+
+                list.sort_by(|left, right| match (left, right) {
+                    (RoxyType::Number(left), RoxyType::Number(right)) => left.total_cmp(right),
+                    (RoxyType::String(left), RoxyType::String(right)) => left.cmp(right),
+                    _ => Ordering::Equal,
+                });
+                Ok(RoxyType::NULL)
+            }),
+        ),
+        _ => {
+            return Err(RoxyError::InterpreterError(
+                InterpreterError::UndefinedProperty(name.clone()),
+            ))
+        }
+    };
+
+    return Ok(RoxyType::NativeFunction(NativeFunction {
+        name: name.lexeme.clone(),
+        arity,
+        params: vec![],
+        callable,
+    }));
+}
@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use crate::{
+    utils::errors::{InterpreterError, RoxyError},
+    MapKey, MapRef, NativeFn, NativeFunction, RoxyType, Token,
+};
+
+pub fn key(value: &RoxyType, token: &Token) -> Result<MapKey, RoxyError> {
+    match MapKey::new(value) {
+        Some(key) => return Ok(key),
+        None => {
+            return Err(RoxyError::InterpreterError(
+                InterpreterError::InvalidMapKey(token.clone()),
+            ))
+        }
+    }
+}
+
+pub fn get(map: &MapRef, index: &RoxyType, token: &Token) -> Result<RoxyType, RoxyError> {
+    if let Some(value) = map.borrow().get(&key(index, token)?) {
+        return Ok(value.clone());
+    }
+
+    // Quoted like in a printed map, so that `m["1"]` and `m[1]` report different keys
+    let name = match index {
+        RoxyType::String(streeng) => format!("{:?}", streeng),
+        _ => index.to_string(),
+    };
+    return Err(RoxyError::InterpreterError(InterpreterError::UndefinedKey(
+        name,
+        token.clone(),
+    )));
+}
+
+pub fn set(
+    map: &MapRef,
+    index: &RoxyType,
+    value: RoxyType,
+    token: &Token,
+) -> Result<(), RoxyError> {
+    map.borrow_mut().insert(key(index, token)?, value);
+    return Ok(());
+}
+
+// Bound natives like the list methods, see `list::method`
+pub fn method(map: &MapRef, name: &Token) -> Result<RoxyType, RoxyError> {
+    let map = map.clone();
+    let (arity, callable): (usize, NativeFn) = match name.lexeme.as_str() {
+        "len" => (
+            0,
+            Rc::new(move |_, _, _| Ok(RoxyType::Number(map.borrow().len() as f64))),
+        ),
+        "keys" => (
+            0,
+            Rc::new(move |_, _, _| {
+                let keys: Vec<RoxyType> = map.borrow().iter().map(|(k, _)| k.into()).collect();
+                Ok(RoxyType::from(keys))
+            }),
+        ),
+        "values" => (
+            0,
+            Rc::new(move |_, _, _| {
+                let values: Vec<RoxyType> = map.borrow().iter().map(|(_, v)| v.clone()).collect();
+                Ok(RoxyType::from(values))
+            }),
+        ),
+        "has" => (
+            1,
+            Rc::new(move |_, args: Vec<RoxyType>, token| {
+                let key = key(&args[0], &token)?;
+                Ok(RoxyType::Boolean(map.borrow().contains_key(&key)))
+            }),
+        ),
+        // Hands back the removed value, nil when the key wasn't there
+        "remove" => (
+            1,
+            Rc::new(move |_, args: Vec<RoxyType>, token| {
+                let key = key(&args[0], &token)?;
+                Ok(map.borrow_mut().remove(&key).into())
+            }),
+        ),
+        _ => {
+            return Err(RoxyError::InterpreterError(
+                InterpreterError::UndefinedProperty(name.clone()),
+            ))
+        }
+    };
+
+    return Ok(RoxyType::NativeFunction(NativeFunction {
+        name: name.lexeme.clone(),
+        arity,
+        params: vec![],
+        callable,
+    }));
+}
@@ -1,2 +1,5 @@
 pub mod interpreter;
+pub mod list;
+pub mod map;
+pub mod module;
 pub use interpreter::*;
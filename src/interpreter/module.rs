@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    expr::Stmt,
+    parser::Parser,
+    scanner::Scanner,
+    utils::errors::{InterpreterError, RoxyError},
+    Token,
+};
+
+// Paths in an import are relative to the importing file, or to the working directory for code
+// which doesn't come from a file like the REPL
+pub fn resolve_path(importer: Option<&Path>, path: &str) -> PathBuf {
+    let dir = importer.and_then(|importer| importer.parent());
+    match dir {
+        Some(dir) => return dir.join(path),
+        None => return PathBuf::from(path),
+    }
+}
+
+// How a module shows up in errors and as the name of its namespace
+pub fn display_name(path: &Path) -> String {
+    return path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+}
+
+// Errors from another file can't be pointed at in the importing one, so they are flattened into
+// a message naming the line
+pub fn describe(err: &RoxyError) -> String {
+    match err.span() {
+        Some(span) if span.line > 0 => return format!("{} (line {})", err.message(), span.line),
+        _ => return err.message(),
+    }
+}
+
+pub fn parse(path: &Path, token: &Token) -> Result<Vec<Stmt>, RoxyError> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(_) => {
+            return Err(RoxyError::InterpreterError(
+                InterpreterError::ModuleNotFound(path.display().to_string(), token.clone()),
+            ))
+        }
+    };
+
+    let in_module = |err: RoxyError| {
+        RoxyError::InterpreterError(InterpreterError::ErrorInModule(
+            display_name(path),
+            describe(&err),
+            token.clone(),
+        ))
+    };
+
+    let mut scanner = Scanner::new(source);
+    let tokens = scanner.scan_tokens().map_err(in_module)?;
+    let mut parser = Parser::new(tokens.clone());
+    let stmts = parser.parse().map_err(in_module)?;
+    if let Some(err) = parser.errors.into_iter().next() {
+        return Err(in_module(err));
+    }
+
+    return Ok(stmts);
+}
+
+// Names a module binds at its top level, which are the ones other files can import
+pub fn exported_names(stmts: &[Stmt]) -> Vec<String> {
+    let mut names = vec![];
+    for stmt in stmts {
+        match stmt {
+            Stmt::Class(class) => names.push(class.name.lexeme.clone()),
+            Stmt::Function(function) => names.push(function.name.lexeme.clone()),
+            Stmt::VariableStmt(var_stmt) => names.push(var_stmt.name.lexeme.clone()),
+            Stmt::Import(import) => {
+                if let Some(alias) = &import.alias {
+                    names.push(alias.lexeme.clone());
+                }
+                names.extend(import.names.iter().map(|name| name.lexeme.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    return names;
+}
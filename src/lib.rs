@@ -0,0 +1,647 @@
+#![allow(
+    clippy::needless_return,
+    clippy::module_inception,
+    clippy::result_large_err
+)]
+
+pub mod callable;
+pub mod environment;
+pub mod expr;
+pub mod interpreter;
+pub mod parser;
+pub mod resolver;
+pub mod scanner;
+pub mod span;
+pub mod tokens;
+pub mod utils;
+pub mod vm;
+
+use core::fmt::Debug;
+use environment::{EnvRef, Environment};
+use expr::Stmt;
+use span::Span;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Formatter};
+use std::path::PathBuf;
+use std::rc::Rc;
+use tokens::{TokenType, Trivia};
+
+use interpreter::Interpreter;
+use utils::errors::{InterpreterError, RoxyError};
+
+// TODO: Write tests for every component
+// TODO: Remove all unnecessary comments
+
+//TODO: Move all these into a separate file(most probably interpreter.rs)
+#[derive(Clone)]
+pub enum RoxyType {
+    String(String),
+    Number(f64),
+    NULL,
+    Boolean(bool),
+    Object,
+    RoxyFunction(Rc<RoxyFunction>),
+    NativeFunction(NativeFunction),
+    RoxyClass(Rc<RoxyClass>),
+    RoxyInstance(Rc<RefCell<RoxyInstance>>),
+    List(ListRef),
+    Map(MapRef),
+    Module(Rc<RoxyModule>),
+}
+
+// Lists are shared, every copy of the value sees pushes made through any other
+pub type ListRef = Rc<RefCell<Vec<RoxyType>>>;
+pub type MapRef = Rc<RefCell<RoxyMap>>;
+
+// Body of a function implemented in Rust. The token is the closing paren of the call and is meant
+// for errors. Closures only get shared access to what they capture, keep mutable state behind a
+// `Cell` or `RefCell`
+pub type NativeFn =
+    Rc<dyn Fn(&mut Interpreter, Vec<RoxyType>, Token) -> Result<RoxyType, RoxyError>>;
+
+//TODO: Implement Debug properly for both functions
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    pub params: Vec<Token>,
+    pub callable: NativeFn,
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+impl std::fmt::Display for NativeFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "<{:?}>", self.name)
+    }
+}
+
+#[derive(Clone)]
+pub struct RoxyFunction {
+    pub name: String,
+    pub arity: usize,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+    pub closure: EnvRef,
+    // Top-level environment of the file the function was declared in, global names in the body
+    // are looked up here even when the function is called from another module
+    pub globals: EnvRef,
+    // File the function was declared in, None for code which doesn't come from a file
+    pub file: Option<PathBuf>,
+    pub is_initializer: bool,
+}
+
+// The environments are left out on purpose, a function stored in its own closure would make the
+// derived impl recurse forever
+impl Debug for RoxyFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.debug_struct("RoxyFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("is_initializer", &self.is_initializer)
+            .finish()
+    }
+}
+
+impl RoxyFunction {
+    pub fn bind(&self, instance: &Rc<RefCell<RoxyInstance>>) -> RoxyFunction {
+        let env = Environment::new_ref(Some(self.closure.clone()));
+        env.borrow_mut()
+            .define("this".into(), RoxyType::RoxyInstance(instance.clone()));
+
+        return RoxyFunction {
+            name: self.name.clone(),
+            arity: self.arity,
+            params: self.params.clone(),
+            body: self.body.clone(),
+            closure: env,
+            globals: self.globals.clone(),
+            file: self.file.clone(),
+            is_initializer: self.is_initializer,
+        };
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RoxyClass {
+    pub name: String,
+    pub methods: HashMap<String, RoxyFunction>,
+    pub superclass: Option<Rc<RoxyClass>>,
+}
+
+impl RoxyClass {
+    pub fn find_method(&self, name: String) -> Option<&RoxyFunction> {
+        let local_method_search_opt = self.methods.get(&name);
+        if local_method_search_opt.is_some() {
+            return local_method_search_opt;
+        }
+
+        if let Some(superclass) = &self.superclass {
+            return superclass.find_method(name);
+        }
+
+        return None;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RoxyInstance {
+    pub klass: Rc<RoxyClass>,
+    pub fields: HashMap<String, RoxyType>,
+}
+
+impl RoxyInstance {
+    pub fn new(klass: Rc<RoxyClass>) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            klass,
+            fields: HashMap::new(),
+        }))
+    }
+
+    // Takes the shared handle rather than `&self` because bound methods need to capture the very
+    // same instance as `this`
+    pub fn get(instance: &Rc<RefCell<Self>>, name: Token) -> Result<RoxyType, RoxyError> {
+        if let Some(value) = instance.borrow().fields.get(&name.lexeme) {
+            return Ok(value.to_owned());
+        }
+
+        let klass = instance.borrow().klass.clone();
+        if let Some(method) = klass.find_method(name.lexeme.clone()) {
+            let res = method.bind(instance);
+            return Ok(RoxyType::RoxyFunction(Rc::new(res)));
+        }
+
+        return Err(RoxyError::InterpreterError(
+            InterpreterError::UndefinedProperty(name),
+        ));
+    }
+
+    pub fn set(&mut self, name: Token, value: RoxyType) {
+        self.fields.insert(name.lexeme, value);
+    }
+}
+
+// Namespace created by `import "path.rx" as name;`, its properties are the top-level bindings of
+// the file
+#[derive(Debug)]
+pub struct RoxyModule {
+    pub name: String,
+    pub path: PathBuf,
+    pub env: EnvRef,
+}
+
+impl RoxyModule {
+    pub fn get(&self, name: Token) -> Result<RoxyType, RoxyError> {
+        if let Some(value) = self.env.borrow().values.get(&name.lexeme) {
+            return Ok(value.to_owned());
+        }
+
+        return Err(RoxyError::InterpreterError(
+            InterpreterError::UndefinedProperty(name),
+        ));
+    }
+}
+
+// The values which can be used as map keys. Numbers are hashed by their bits, with `-0` folded
+// into `0` since the two are equal
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MapKey {
+    String(String),
+    Number(u64),
+    Boolean(bool),
+    NULL,
+}
+
+impl MapKey {
+    pub fn new(value: &RoxyType) -> Option<Self> {
+        match value {
+            RoxyType::String(val) => Some(MapKey::String(val.clone())),
+            RoxyType::Number(val) if *val == 0.0 => Some(MapKey::Number(0f64.to_bits())),
+            RoxyType::Number(val) => Some(MapKey::Number(val.to_bits())),
+            RoxyType::Boolean(val) => Some(MapKey::Boolean(*val)),
+            RoxyType::NULL => Some(MapKey::NULL),
+            _ => None,
+        }
+    }
+}
+
+impl From<&MapKey> for RoxyType {
+    fn from(key: &MapKey) -> Self {
+        match key {
+            MapKey::String(val) => RoxyType::String(val.clone()),
+            MapKey::Number(bits) => RoxyType::Number(f64::from_bits(*bits)),
+            MapKey::Boolean(val) => RoxyType::Boolean(*val),
+            MapKey::NULL => RoxyType::NULL,
+        }
+    }
+}
+
+// Entries are kept in insertion order, which is the order `keys`, `values` and printing use.
+// Assigning to an existing key keeps its position
+#[derive(Clone, Debug, Default)]
+pub struct RoxyMap {
+    entries: Vec<(MapKey, RoxyType)>,
+    positions: HashMap<MapKey, usize>,
+}
+
+impl RoxyMap {
+    pub fn get(&self, key: &MapKey) -> Option<&RoxyType> {
+        let position = self.positions.get(key)?;
+        return Some(&self.entries[*position].1);
+    }
+
+    pub fn insert(&mut self, key: MapKey, value: RoxyType) {
+        match self.positions.get(&key) {
+            Some(position) => self.entries[*position].1 = value,
+            None => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &MapKey) -> Option<RoxyType> {
+        let position = self.positions.remove(key)?;
+        let (_, value) = self.entries.remove(position);
+        for (key, _) in &self.entries[position..] {
+            if let Some(later) = self.positions.get_mut(key) {
+                *later -= 1;
+            }
+        }
+
+        return Some(value);
+    }
+
+    pub fn contains_key(&self, key: &MapKey) -> bool {
+        return self.positions.contains_key(key);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&MapKey, &RoxyType)> {
+        return self.entries.iter().map(|(key, value)| (key, value));
+    }
+}
+
+impl RoxyType {
+    // Only `nil` and `false` are falsy, every other value including `0` and `""` is truthy
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, RoxyType::NULL | RoxyType::Boolean(false))
+    }
+
+    // Strings inside a collection are quoted, so that `["1", 1]` doesn't print as `[1, 1]`.
+    // `printing` holds the collections being printed further out, one which contains itself
+    // prints as `[...]` or `{...}` there instead of recursing forever
+    fn fmt_nested(
+        &self,
+        f: &mut Formatter<'_>,
+        printing: &mut Vec<*const ()>,
+    ) -> Result<(), std::fmt::Error> {
+        match self {
+            RoxyType::String(streeng) => write!(f, "{:?}", streeng),
+            RoxyType::List(list) => RoxyType::fmt_list(list, f, printing),
+            RoxyType::Map(map) => RoxyType::fmt_map(map, f, printing),
+            _ => write!(f, "{}", self),
+        }
+    }
+
+    fn fmt_list(
+        list: &ListRef,
+        f: &mut Formatter<'_>,
+        printing: &mut Vec<*const ()>,
+    ) -> Result<(), std::fmt::Error> {
+        let pointer = Rc::as_ptr(list) as *const ();
+        if printing.contains(&pointer) {
+            return write!(f, "[...]");
+        }
+
+        printing.push(pointer);
+        write!(f, "[")?;
+        for (i, element) in list.borrow().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            element.fmt_nested(f, printing)?;
+        }
+        printing.pop();
+        return write!(f, "]");
+    }
+
+    fn fmt_map(
+        map: &MapRef,
+        f: &mut Formatter<'_>,
+        printing: &mut Vec<*const ()>,
+    ) -> Result<(), std::fmt::Error> {
+        let pointer = Rc::as_ptr(map) as *const ();
+        if printing.contains(&pointer) {
+            return write!(f, "{{...}}");
+        }
+
+        printing.push(pointer);
+        write!(f, "{{")?;
+        for (i, (key, value)) in map.borrow().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            RoxyType::from(key).fmt_nested(f, printing)?;
+            write!(f, ": ")?;
+            value.fmt_nested(f, printing)?;
+        }
+        printing.pop();
+        return write!(f, "}}");
+    }
+}
+
+// Values of different types are never equal. Strings, numbers and booleans compare by value while
+// functions, classes, instances, lists, maps and modules compare by identity
+impl PartialEq for RoxyType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (RoxyType::String(val_left), RoxyType::String(val_right)) => val_left == val_right,
+            (RoxyType::Number(val_left), RoxyType::Number(val_right)) => val_left == val_right,
+            (RoxyType::NULL, RoxyType::NULL) => true,
+            (RoxyType::Boolean(val_left), RoxyType::Boolean(val_right)) => val_left == val_right,
+            (RoxyType::RoxyFunction(left), RoxyType::RoxyFunction(right)) => {
+                Rc::ptr_eq(left, right)
+            }
+            (RoxyType::NativeFunction(left), RoxyType::NativeFunction(right)) => {
+                Rc::ptr_eq(&left.callable, &right.callable)
+            }
+            (RoxyType::RoxyClass(left), RoxyType::RoxyClass(right)) => Rc::ptr_eq(left, right),
+            (RoxyType::RoxyInstance(left), RoxyType::RoxyInstance(right)) => {
+                Rc::ptr_eq(left, right)
+            }
+            (RoxyType::List(left), RoxyType::List(right)) => Rc::ptr_eq(left, right),
+            (RoxyType::Map(left), RoxyType::Map(right)) => Rc::ptr_eq(left, right),
+            (RoxyType::Module(left), RoxyType::Module(right)) => Rc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+// Lists, maps and instances can hold themselves, a derived impl would recurse forever on those.
+// Instances and modules are shown by name, collections the way they are printed
+impl Debug for RoxyType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            RoxyType::String(val) => f.debug_tuple("String").field(val).finish(),
+            RoxyType::Number(val) => f.debug_tuple("Number").field(val).finish(),
+            RoxyType::NULL => write!(f, "NULL"),
+            RoxyType::Boolean(val) => f.debug_tuple("Boolean").field(val).finish(),
+            RoxyType::Object => write!(f, "Object"),
+            RoxyType::RoxyFunction(roxy_fn) => {
+                f.debug_tuple("RoxyFunction").field(roxy_fn).finish()
+            }
+            RoxyType::NativeFunction(native_fn) => {
+                f.debug_tuple("NativeFunction").field(native_fn).finish()
+            }
+            RoxyType::RoxyClass(roxy_class) => write!(f, "RoxyClass({})", roxy_class.name),
+            RoxyType::RoxyInstance(roxy_instance) => {
+                write!(f, "RoxyInstance({})", roxy_instance.borrow().klass.name)
+            }
+            RoxyType::List(_) => write!(f, "List({})", self),
+            RoxyType::Map(_) => write!(f, "Map({})", self),
+            RoxyType::Module(module) => write!(f, "Module({})", module.name),
+        }
+    }
+}
+
+impl std::fmt::Display for RoxyType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        //TODO: Improve Object part
+        // Same output as the reference implementation, `print` goes through here
+        match self {
+            RoxyType::String(streeng) => write!(f, "{}", streeng),
+            RoxyType::Number(no) => write!(f, "{}", no),
+            RoxyType::NULL => write!(f, "nil"),
+            RoxyType::Boolean(boole) => write!(f, "{:?}", boole),
+            RoxyType::Object => write!(f, "object"),
+            RoxyType::RoxyFunction(roxy_fn) => write!(f, "<fn {}>", roxy_fn.name),
+            RoxyType::NativeFunction(_) => write!(f, "<native fn>"),
+            RoxyType::RoxyClass(roxy_class) => write!(f, "{}", roxy_class.name),
+            RoxyType::RoxyInstance(roxy_instance) => {
+                write!(f, "{} instance", roxy_instance.borrow().klass.name)
+            }
+            RoxyType::List(list) => RoxyType::fmt_list(list, f, &mut vec![]),
+            RoxyType::Map(map) => RoxyType::fmt_map(map, f, &mut vec![]),
+            RoxyType::Module(module) => write!(f, "<module {}>", module.name),
+        }
+    }
+}
+
+trait TryConversion<T>: Sized {
+    fn try_conversion(value: RoxyType, token: Token) -> Result<T, RoxyError>;
+}
+
+impl TryConversion<f64> for f64 {
+    fn try_conversion(value: RoxyType, token: Token) -> Result<Self, RoxyError> {
+        match value {
+            RoxyType::Number(val) => Ok(val),
+            _ => Err(RoxyError::InterpreterError(
+                InterpreterError::InvalidNumberCast(token),
+            )),
+        }
+    }
+}
+
+// Every value has a truthiness, so this never fails
+impl TryConversion<bool> for bool {
+    fn try_conversion(value: RoxyType, _: Token) -> Result<Self, RoxyError> {
+        Ok(value.is_truthy())
+    }
+}
+
+impl TryConversion<String> for String {
+    fn try_conversion(value: RoxyType, token: Token) -> Result<Self, RoxyError> {
+        match value {
+            RoxyType::String(val) => Ok(val),
+            _ => Err(RoxyError::InterpreterError(
+                InterpreterError::InvalidStringCast(token),
+            )),
+        }
+    }
+}
+
+// Conversions for embedding hosts. Numbers are always `f64` in Lox, so integers are widened
+impl From<f64> for RoxyType {
+    fn from(value: f64) -> Self {
+        RoxyType::Number(value)
+    }
+}
+
+impl From<i64> for RoxyType {
+    fn from(value: i64) -> Self {
+        RoxyType::Number(value as f64)
+    }
+}
+
+impl From<i32> for RoxyType {
+    fn from(value: i32) -> Self {
+        RoxyType::Number(value as f64)
+    }
+}
+
+impl From<bool> for RoxyType {
+    fn from(value: bool) -> Self {
+        RoxyType::Boolean(value)
+    }
+}
+
+impl From<String> for RoxyType {
+    fn from(value: String) -> Self {
+        RoxyType::String(value)
+    }
+}
+
+impl From<&str> for RoxyType {
+    fn from(value: &str) -> Self {
+        RoxyType::String(value.to_string())
+    }
+}
+
+impl From<()> for RoxyType {
+    fn from(_: ()) -> Self {
+        RoxyType::NULL
+    }
+}
+
+impl<T: Into<RoxyType>> From<Option<T>> for RoxyType {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => RoxyType::NULL,
+        }
+    }
+}
+
+impl<T: Into<RoxyType>> From<Vec<T>> for RoxyType {
+    fn from(value: Vec<T>) -> Self {
+        let elements = value.into_iter().map(|element| element.into()).collect();
+        RoxyType::List(Rc::new(RefCell::new(elements)))
+    }
+}
+
+// The value is handed back untouched when it holds a different type
+impl TryFrom<RoxyType> for f64 {
+    type Error = RoxyType;
+
+    fn try_from(value: RoxyType) -> Result<Self, Self::Error> {
+        match value {
+            RoxyType::Number(val) => Ok(val),
+            _ => Err(value),
+        }
+    }
+}
+
+impl TryFrom<RoxyType> for bool {
+    type Error = RoxyType;
+
+    fn try_from(value: RoxyType) -> Result<Self, Self::Error> {
+        match value {
+            RoxyType::Boolean(val) => Ok(val),
+            _ => Err(value),
+        }
+    }
+}
+
+impl TryFrom<RoxyType> for String {
+    type Error = RoxyType;
+
+    fn try_from(value: RoxyType) -> Result<Self, Self::Error> {
+        match value {
+            RoxyType::String(val) => Ok(val),
+            _ => Err(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    token_type: TokenType,
+    lexeme: String,
+    literal: RoxyType,
+    line: usize,
+    span: Span,
+    // Whitespace and comments around the token, empty unless the scanner keeps trivia
+    leading_trivia: Vec<Trivia>,
+    trailing_trivia: Vec<Trivia>,
+}
+
+impl Token {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn token_type(&self) -> &TokenType {
+        &self.token_type
+    }
+
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+
+    pub fn literal(&self) -> &RoxyType {
+        &self.literal
+    }
+
+    pub fn leading_trivia(&self) -> &[Trivia] {
+        &self.leading_trivia
+    }
+
+    pub fn trailing_trivia(&self) -> &[Trivia] {
+        &self.trailing_trivia
+    }
+
+    // The token as it was written, with its trivia. Joining these for every token of a lossless
+    // scan gives back the source
+    pub fn full_text(&self) -> String {
+        let mut text = String::new();
+        for trivia in &self.leading_trivia {
+            text.push_str(&trivia.text);
+        }
+        text.push_str(&self.lexeme);
+        for trivia in &self.trailing_trivia {
+            text.push_str(&trivia.text);
+        }
+        return text;
+    }
+
+    // Token for code which does not come from any source, like calls made by an embedding host
+    pub fn synthetic(token_type: TokenType, lexeme: &str) -> Self {
+        Self {
+            token_type,
+            lexeme: lexeme.to_string(),
+            literal: RoxyType::NULL,
+            line: 0,
+            span: Span::default(),
+            leading_trivia: vec![],
+            trailing_trivia: vec![],
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{:?} {:?} {:?}",
+            self.token_type.to_string().as_str().to_owned(),
+            self.lexeme.as_str(),
+            self.literal.to_string().as_str(),
+        )
+    }
+}
@@ -1,402 +1,391 @@
-pub mod callable;
-pub mod environment;
-pub mod expr;
-pub mod interpreter;
-pub mod parser;
-pub mod resolver;
-pub mod scanner;
-pub mod tokens;
-pub mod utils;
-
-use core::fmt::Debug;
-use environment::Environment;
-use expr::Stmt;
-use parser::Parser;
-use resolver::Resolver;
-use std::collections::HashMap;
-use std::fmt::{self, Formatter};
-use tokens::TokenType;
-
-use ctrlc;
-use interpreter::Interpreter;
-use std::io::Write;
-use std::{env::args, fs, process::exit};
-use utils::errors::{InterpreterError, RoxyError};
-
-// TODO: Write tests for every component
-// TODO: Remove all unnecessary comments
-
-//TODO: Move all these into a separate file(most probably interpreter.rs)
-#[derive(Debug, Clone)]
-pub enum RoxyType {
-    String(String),
-    Number(f64),
-    NULL,
-    Boolean(bool),
-    Object,
-    RoxyFunction(RoxyFunction),
-    NativeFunction(NativeFunction),
-    RoxyClass(RoxyClass),
-    RoxyInstance(RoxyInstance),
-}
+#![allow(clippy::needless_return)]
+
+use clap::{App, Arg};
+use roxy::expr;
+use roxy::scanner;
+use roxy::utils::diagnostics::{display_path, DiagnosticRenderer, ErrorFormat};
+use roxy::utils::errors::{EvalError, RoxyError};
+use roxy::vm::Vm;
+use rustyline::{error::ReadlineError, DefaultEditor};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Instant;
+use std::{fs, process::exit};
 
-//TODO: Implement Debug properly for both functions
-#[derive(Clone)]
-pub struct NativeFunction {
-    pub name: String,
-    pub arity: usize,
-    pub params: Vec<Token>,
-    pub callable: fn(
-        roxy_type: &RoxyType,
-        interpreter: &mut Interpreter,
-        arguments: Vec<RoxyType>,
-        token: Token,
-    ) -> Result<RoxyType, RoxyError>,
+struct CliHandler {
+    had_err: bool,
+    had_runtime_err: bool,
+    color: bool,
+    error_format: ErrorFormat,
 }
 
-impl Debug for NativeFunction {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        f.debug_struct("NativeFunction")
-            .field("name", &self.name)
-            .field("arity", &self.arity)
-            .finish()
+impl CliHandler {
+    fn new(color: bool, error_format: ErrorFormat) -> Self {
+        Self {
+            had_err: false,
+            had_runtime_err: false,
+            color,
+            error_format,
+        }
     }
-}
 
-impl std::fmt::Display for NativeFunction {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(f, "<{:?}>", self.name)
-    }
-}
+    fn run_prompt(&mut self) -> anyhow::Result<()> {
+        println!("Running Prompt");
+        let mut editor = DefaultEditor::new()?;
+        let history = CliHandler::history_path();
+        if let Some(history) = &history {
+            // Missing on the first run
+            let _ = editor.load_history(history);
+        }
 
-#[derive(Clone, Debug)]
-pub struct RoxyFunction {
-    pub name: String,
-    pub arity: usize,
-    pub params: Vec<Token>,
-    pub body: Vec<Stmt>,
-    pub closure: Option<Box<Environment>>,
-    pub is_method: bool,
-    pub is_initializer: bool,
-}
+        // One vm for the whole session so definitions made on a line are visible to the following
+        // ones. A resolver is still created per line, at the top level it holds no scopes and
+        // globals are looked up dynamically
+        let mut vm = Vm::new();
+        let mut buffer = String::new();
 
-impl RoxyFunction {
-    pub fn bind(&self, instance: &RoxyInstance) -> RoxyFunction {
-        let mut env = Environment::new_with_enclosing(self.closure.clone().unwrap());
-        env.define(
-            "this".into(),
-            RoxyType::RoxyInstance(instance.to_owned()),
-            None,
-        );
-
-        return RoxyFunction {
-            name: self.name.clone(),
-            arity: self.arity,
-            params: self.params.clone(),
-            body: self.body.clone(),
-            closure: Some(Box::new(env)),
-            is_method: true,
-            is_initializer: self.is_initializer,
-        };
-    }
-}
+        loop {
+            let prompt = if buffer.is_empty() { "> " } else { "... " };
+            match editor.readline(prompt) {
+                Ok(line) => {
+                    buffer.push_str(&line);
+                    buffer.push('\n');
+                    // Keep reading while a block, call, grouping or string is left open
+                    if open_delimiters(&buffer) > 0 {
+                        continue;
+                    }
 
-#[derive(Clone, Debug)]
-pub struct RoxyClass {
-    pub name: String,
-    pub methods: HashMap<String, RoxyFunction>,
-    pub superclass: Option<Box<RoxyClass>>,
-}
+                    let input = std::mem::take(&mut buffer).trim().to_string();
+                    if input.is_empty() {
+                        continue;
+                    }
+                    editor.add_history_entry(input.as_str())?;
 
-impl RoxyClass {
-    pub fn find_method(&self, name: String) -> Option<&RoxyFunction> {
-        let local_method_search_opt = self.methods.get(&name);
-        if local_method_search_opt.is_some() {
-            return local_method_search_opt;
-        } else {
-            if let Some(superclass) = &self.superclass {
-                return superclass.find_method(name);
+                    if input == "exit" {
+                        break;
+                    }
+
+                    match input.strip_prefix(':') {
+                        Some(command) => self.run_meta_command(command, &mut vm),
+                        None => self.run_line(input, &mut vm),
+                    }
+                    self.had_err = false;
+                    self.had_runtime_err = false;
+                }
+                // Ctrl-C throws away what has been typed so far
+                Err(ReadlineError::Interrupted) => buffer.clear(),
+                // Ctrl-D
+                Err(ReadlineError::Eof) => break,
+                Err(err) => return Err(err.into()),
             }
+        }
 
-            return None;
+        if let Some(history) = &history {
+            // Losing the history should not turn a clean exit into a failure
+            if let Err(err) = editor.save_history(history) {
+                eprintln!("Could not save history to {}: {}", history.display(), err);
+            }
         }
+
+        Ok(())
     }
-}
 
-#[derive(Clone, Debug)]
-pub struct RoxyInstance {
-    pub klass: RoxyClass,
-    pub fields: HashMap<String, RoxyType>,
-}
+    fn history_path() -> Option<PathBuf> {
+        return std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".roxy_history"));
+    }
 
-impl RoxyInstance {
-    pub fn get(&mut self, name: Token) -> Result<RoxyType, RoxyError> {
-        if let Some(value) = self.fields.get(&name.lexeme) {
-            return Ok(value.to_owned());
-        }
+    fn run_file(&mut self, file_name: &str) -> anyhow::Result<()> {
+        let contents = fs::read_to_string(file_name)?;
+        let mut vm = Vm::new();
+        vm.set_file(file_name);
+        self.run(contents, file_name, &mut vm);
 
-        if let Some(method) = self.klass.find_method(name.lexeme.clone()) {
-            let res = method.bind(&self);
-            return Ok(RoxyType::RoxyFunction(res));
+        if self.had_err {
+            exit(65);
         }
 
-        return Err(RoxyError::InterpreterError(
-            InterpreterError::UndefinedProperty(name),
-        ));
-    }
+        if self.had_runtime_err {
+            exit(70);
+        }
 
-    pub fn set(&mut self, name: Token, value: RoxyType) {
-        self.fields.insert(name.lexeme, value);
+        Ok(())
     }
-}
 
-impl PartialEq for RoxyType {
-    fn eq(&self, other: &Self) -> bool {
-        //TODO: Confirm if objects case is rightly implemented
-        match (self, other) {
-            (RoxyType::String(val_left), RoxyType::String(val_right)) => val_left == val_right,
-            (RoxyType::Number(val_left), RoxyType::Number(val_right)) => val_left == val_right,
-            (RoxyType::NULL, RoxyType::NULL) => true,
-            (RoxyType::NULL, RoxyType::Boolean(val)) => &false == val,
-            (RoxyType::Boolean(val), RoxyType::NULL) => val == &true,
-            (RoxyType::Boolean(val_left), RoxyType::Boolean(val_right)) => val_left == val_right,
-            _ => false,
-        }
-    }
-}
+    // Commands for poking at the session, they start with a `:` so they can not clash with code
+    fn run_meta_command(&mut self, command: &str, vm: &mut Vm) {
+        let (name, arg) = match command.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (command, ""),
+        };
 
-impl std::fmt::Display for RoxyType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        //TODO: Improve Object part
-        //TODO: Improve NativeFunc & RoxyFunction part
-        match self {
-            RoxyType::String(streeng) => write!(f, "{}", streeng),
-            RoxyType::Number(no) => write!(f, "{}", no),
-            RoxyType::NULL => write!(f, "NULL"),
-            RoxyType::Boolean(boole) => write!(f, "{:?}", boole),
-            RoxyType::Object => write!(f, "object"),
-            RoxyType::RoxyFunction(_) => write!(f, "RoxyFunction"),
-            RoxyType::NativeFunction(_) => write!(f, "NativeFunction"),
-            RoxyType::RoxyClass(_) => write!(f, "RoxyClass"),
-            RoxyType::RoxyInstance(roxy_instance) => {
-                write!(f, "{:?} instance", roxy_instance.klass.name)
+        match name {
+            "tokens" => {
+                let mut scanner = scanner::Scanner::new(arg.to_string());
+                match scanner.scan_tokens() {
+                    Ok(tokens) => {
+                        for token in tokens {
+                            println!("{:<12} {}", token.span().to_string(), token);
+                        }
+                    }
+                    Err(err) => self.report(&err, "<prompt>", arg),
+                }
             }
-        }
-    }
-}
+            "ast" => match Vm::parse(arg) {
+                Ok(stmts) => print!("{}", expr::pretty_print_all(&stmts, 0)),
+                Err(err) => self.report_all(err, "<prompt>", arg),
+            },
+            "env" => {
+                let interpreter = vm.interpreter();
+                let mut env = Some(interpreter.environment.clone());
+                let mut depth = 0;
+                while let Some(current) = env {
+                    if Rc::ptr_eq(&current, &interpreter.globals) {
+                        println!("globals:");
+                    } else if Rc::ptr_eq(&current, &interpreter.builtins) {
+                        println!("builtins:");
+                    } else {
+                        println!("scope {}:", depth);
+                    }
 
-trait TryConversion<T>: Sized {
-    fn try_conversion(value: RoxyType, token: Token) -> Result<T, RoxyError>;
-}
+                    let current = current.borrow();
+                    let mut names: Vec<&String> = current.values.keys().collect();
+                    names.sort();
+                    for name in names {
+                        println!("  {} = {}", name, current.values[name]);
+                    }
 
-impl TryConversion<f64> for f64 {
-    fn try_conversion(value: RoxyType, token: Token) -> Result<Self, RoxyError> {
-        match value {
-            RoxyType::Number(val) => Ok(val),
-            _ => Err(RoxyError::InterpreterError(
-                InterpreterError::InvalidNumberCast(token),
-            )),
+                    env = current.enclosing.clone();
+                    depth += 1;
+                }
+            }
+            "load" => match fs::read_to_string(arg) {
+                Ok(contents) => {
+                    vm.set_file(arg);
+                    self.run(contents, arg, vm);
+                }
+                Err(err) => println!("Could not load {}: {}", arg, err),
+            },
+            "reset" => *vm = Vm::new(),
+            "time" => {
+                let start = Instant::now();
+                self.run_line(arg.to_string(), vm);
+                println!("took {:?}", start.elapsed());
+            }
+            _ => {
+                if name != "help" {
+                    println!("Unknown command `:{}`", name);
+                }
+                println!("Available commands are:");
+                println!("  :tokens <code>  print the tokens the scanner produces");
+                println!("  :ast <code>     print the parsed statements");
+                println!("  :env            print every binding of the session");
+                println!("  :load <file>    run a file inside the session");
+                println!("  :reset          forget everything defined so far");
+                println!("  :time <code>    run code and print how long it took");
+            }
         }
     }
-}
 
-impl TryConversion<bool> for bool {
-    fn try_conversion(value: RoxyType, token: Token) -> Result<Self, RoxyError> {
-        match value {
-            RoxyType::Boolean(val) => Ok(val),
-            RoxyType::NULL => Ok(false),
-            RoxyType::String(_) => Ok(true),
-            _ => Err(RoxyError::InterpreterError(
-                InterpreterError::InvalidBooleanCast(token),
-            )),
+    // Input which is a lone expression gets evaluated and its value echoed, anything else is run
+    // as statements like a file would be
+    fn run_line(&mut self, line: String, vm: &mut Vm) {
+        match vm.eval_expression(&line) {
+            Ok(Some(value)) => println!("{}", value),
+            Ok(None) => self.run(line, "<prompt>", vm),
+            Err(err) => self.report_all(err, "<prompt>", &line),
         }
     }
-}
 
-impl TryConversion<String> for String {
-    fn try_conversion(value: RoxyType, token: Token) -> Result<Self, RoxyError> {
-        match value {
-            RoxyType::String(val) => Ok(val),
-            _ => Err(RoxyError::InterpreterError(
-                InterpreterError::InvalidStringCast(token),
-            )),
+    fn run(&mut self, contents: String, file_name: &str, vm: &mut Vm) {
+        if let Err(err) = vm.run(&contents) {
+            self.report_all(err, file_name, &contents);
         }
     }
-}
-
-#[derive(Debug, Clone, PartialEq)]
-pub struct Token {
-    token_type: TokenType,
-    lexeme: String,
-    literal: RoxyType,
-    line: usize,
-}
-
-impl Token {
-    // fn new(token_type: TokenType, lexeme: String, literal: RoxyType, line: usize) -> Self {
-    //     Self {
-    //         token_type,
-    //         lexeme,
-    //         literal,
-    //         line,
-    //     }
-    // }
-}
-
-impl fmt::Display for Token {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(
-            f,
-            "{:?} {:?} {:?}",
-            self.token_type.to_string().as_str().to_owned(),
-            self.lexeme.as_str(),
-            self.literal.to_string().as_str(),
-        )
-    }
-}
 
-struct CliHandler {
-    had_err: bool,
-    had_runtime_err: bool,
-}
-
-impl CliHandler {
-    fn new() -> Self {
-        Self {
-            had_err: false,
-            had_runtime_err: false,
-            // error: None,
+    fn report_all(&mut self, err: EvalError, file_name: &str, source: &str) {
+        // A runtime error raised in a function of another module points into that module
+        let raised_in = err.trace.first().and_then(|frame| frame.file.as_deref());
+        let renderer = DiagnosticRenderer::new(file_name, source);
+        let (error_file, error_source) = match raised_in {
+            Some(path) if !renderer.is_file(path) => (
+                display_path(path),
+                fs::read_to_string(path).unwrap_or_default(),
+            ),
+            _ => (file_name.to_string(), source.to_string()),
+        };
+        for error in &err.errors {
+            self.report(error, &error_file, &error_source);
         }
-    }
 
-    fn run_prompt(&mut self) -> anyhow::Result<()> {
-        println!("Running Prompt");
-        //TODO: Later check how to convert it into ctrl+d
-        ctrlc::set_handler(move || {
-            println!("exiting because of press of ctrl+c");
-            exit(64);
-        })?;
-
-        loop {
-            let input = CliHandler::prompt("> ");
-
-            if input == "exit" {
-                break;
-            } else if input != "" {
-                self.run(input)?;
-                self.had_err = false;
-            }
+        // Only runtime errors have a trace, and there is just the one when there is
+        if let (Some(error), ErrorFormat::Human) = (err.errors.first(), self.error_format) {
+            let renderer = DiagnosticRenderer::new(file_name, source).with_color(self.color);
+            eprint!("{}", renderer.render_trace(error, &err.trace));
         }
-
-        Ok(())
     }
 
-    fn run_file(&self, file_name: &str) -> anyhow::Result<()> {
-        let contents = fs::read_to_string(file_name)?;
-        self.run(contents)?;
-
-        if self.had_err {
-            exit(65);
+    fn report(&mut self, err: &RoxyError, file_name: &str, source: &str) {
+        if err.is_runtime() {
+            self.had_runtime_err = true;
+        } else {
+            self.had_err = true;
         }
 
-        if self.had_runtime_err {
-            exit(70);
+        let renderer = DiagnosticRenderer::new(file_name, source).with_color(self.color);
+        match self.error_format {
+            ErrorFormat::Human => eprint!("{}", renderer.render(err)),
+            ErrorFormat::Json => eprint!("{}", renderer.render_json(err)),
         }
-
-        Ok(())
     }
+}
 
-    fn run(&self, contents: String) -> Result<(), RoxyError> {
-        let mut scanner = scanner::Scanner::new(contents.clone());
-        let tokens = scanner.scan_tokens()?;
-
-        // println!("{:?}", tokens);
-        // for token in tokens {
-        //     print!("{:?} - ", token.lexeme);
-        // }
-        // println!("\n=========================");
-
-        //TODO: Enable supoprt for expr execution
-        // let mut parser = Parser::new(tokens.clone());
-        // match parser.parse_expression() {
-        //     Ok(expr_opt) => {
-        //         if let Some(expr) = expr_opt {
-        //             println!("Parsing expr successful");
-        //             let mut interpreter = Interpreter::new();
-        //             match interpreter.evaluate(&expr) {
-        //                 Ok(expr) => {
-        //                     println!("Expr: {:?}", expr);
-        //                 }
-        //                 Err(err) => {
-        //                     println!("Runtime Error: {:?}", err.to_string())
-        //                 }
-        //             }
-        //         } else {
-        //             println!("Parsing Expr Unsuccessful");
-        //             for parsing_error in parser.errors {
-        //                 println!("Expr error: {:?}", parsing_error);
-        //             }
-        //         }
-        //     }
-        //     Err(err) => {
-        //         println!(
-        //             "Parsing Expr Unsuccessful Critical Error: {:?}",
-        //             err.to_string()
-        //         );
-        //     }
-        // }
-
-        let mut parser = Parser::new(tokens.clone());
-        match parser.parse() {
-            Ok(stmts_opt) => match stmts_opt {
-                Some(stmts) => {
-                    // println!("Parsing Successful: {:?}", stmts);
-                    let mut interpreter = Interpreter::new();
-                    let mut resolver = Resolver::new(&mut interpreter);
-                    resolver.resolve(stmts.clone())?;
-                    for stmt in stmts {
-                        interpreter.interpret(stmt)?;
+// Number of `(`, `{` and `[` which are not closed yet, delimiters inside strings and comments are
+// not counted. It follows the scanner's rules for strings and comments, a string, interpolation or
+// block comment which isn't closed yet counts as one more. Negative when there are more closing
+// delimiters, the parser reports those
+fn open_delimiters(source: &str) -> i32 {
+    let chars: Vec<char> = source.chars().collect();
+    let at = |i: usize| chars.get(i).copied();
+    let mut depth = 0;
+    let mut i = 0;
+    // Raw and triple-quoted flags of the string being read, None outside of strings
+    let mut string: Option<(bool, bool)> = None;
+    // Strings we are in a `${` of, with whether they are triple-quoted and the braces opened since
+    let mut interpolations: Vec<(bool, i32)> = vec![];
+
+    loop {
+        if let Some((raw, triple)) = string {
+            match at(i) {
+                None => return depth + 1,
+                Some('\\') if !raw => i += 2,
+                Some('$') if !raw && at(i + 1) == Some('{') => {
+                    interpolations.push((triple, 0));
+                    depth += 1;
+                    string = None;
+                    i += 2;
+                }
+                Some('"') if !triple => {
+                    string = None;
+                    i += 1;
+                }
+                Some('"') if at(i + 1) == Some('"') && at(i + 2) == Some('"') => {
+                    string = None;
+                    i += 3;
+                }
+                Some(_) => i += 1,
+            }
+            continue;
+        }
+
+        let c = match at(i) {
+            Some(c) => c,
+            None => return depth,
+        };
+        i += 1;
+
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            '{' => {
+                depth += 1;
+                if let Some((_, braces)) = interpolations.last_mut() {
+                    *braces += 1;
+                }
+            }
+            '}' => {
+                depth -= 1;
+                match interpolations.last_mut() {
+                    Some((triple, 0)) => {
+                        string = Some((false, *triple));
+                        interpolations.pop();
                     }
+                    Some((_, braces)) => *braces -= 1,
+                    None => (),
                 }
-                None => {
-                    println!("Parsing Statement Unsuccessful");
-                    for parsing_error in parser.errors {
-                        println!("{:?}", parsing_error);
+            }
+            '"' | 'r' if c == '"' || at(i) == Some('"') => {
+                let raw = c == 'r';
+                if raw {
+                    i += 1;
+                }
+                let triple = at(i) == Some('"') && at(i + 1) == Some('"');
+                if triple {
+                    i += 2;
+                }
+                string = Some((raw, triple));
+            }
+            // Whole words are skipped, so an `r` is only the start of a raw string on its own
+            c if c.is_alphanumeric() || c == '_' => {
+                while at(i).is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                    i += 1;
+                }
+            }
+            '/' if at(i) == Some('/') => {
+                while at(i).is_some_and(|c| c != '\n') {
+                    i += 1;
+                }
+            }
+            // Block comments nest
+            '/' if at(i) == Some('*') => {
+                i += 1;
+                let mut nesting = 1;
+                while nesting > 0 {
+                    match (at(i), at(i + 1)) {
+                        (None, _) => return depth + 1,
+                        (Some('/'), Some('*')) => {
+                            nesting += 1;
+                            i += 2;
+                        }
+                        (Some('*'), Some('/')) => {
+                            nesting -= 1;
+                            i += 2;
+                        }
+                        _ => i += 1,
                     }
                 }
-            },
-            Err(err) => {
-                println!("Parsing Statement Unsuccessful Critical Error");
-                return Err(err);
             }
+            _ => (),
         }
-
-        Ok(())
-    }
-
-    fn prompt(name: &str) -> String {
-        let mut line = String::new();
-        print!("{}", name);
-        std::io::stdout().flush().unwrap();
-        std::io::stdin()
-            .read_line(&mut line)
-            .expect("Error: Could not read a line");
-
-        return line.trim().to_string();
     }
 }
 
 fn main() {
-    let args_n = args().len() - 1;
-    let arguments: Vec<String> = args().collect();
+    let matches = App::new("roxy")
+        .about("Tree-walk interpreter for the lox language")
+        .arg(
+            Arg::with_name("script")
+                .help("Script to run, starts a prompt when left out")
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("no-color")
+                .long("no-color")
+                .help("Print errors without colors, also respects NO_COLOR"),
+        )
+        .arg(
+            Arg::with_name("error-format")
+                .long("error-format")
+                .takes_value(true)
+                .possible_values(&["human", "json"])
+                .default_value("human")
+                .help("How errors are printed, `json` prints one object per line"),
+        )
+        .get_matches();
+
+    let color = !matches.is_present("no-color")
+        && std::env::var_os("NO_COLOR").is_none()
+        && std::io::stderr().is_terminal();
 
-    let mut interpreter = CliHandler::new();
+    let error_format = matches
+        .value_of("error-format")
+        .and_then(ErrorFormat::from_name)
+        .unwrap_or(ErrorFormat::Human);
 
-    if args_n > 1 {
-        println!("Usage: roxy [script]");
-        exit(64);
-    } else if args_n == 1 {
-        match interpreter.run_file(&arguments[1]) {
+    let mut interpreter = CliHandler::new(color, error_format);
+
+    if let Some(file_name) = matches.value_of("script") {
+        match interpreter.run_file(file_name) {
             Ok(_) => (),
             // TODO: Confirm if this code is proper for exit with this error
             Err(err) => {
@@ -415,3 +404,71 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::open_delimiters;
+
+    #[test]
+    fn counts_unclosed_delimiters() {
+        assert_eq!(open_delimiters("print (1 + 2);"), 0);
+        assert_eq!(open_delimiters("fun f() {"), 1);
+        assert_eq!(open_delimiters("var xs = [[1, 2], ("), 2);
+        assert_eq!(open_delimiters("if (x) {\n  while (y) {\n  }"), 1);
+    }
+
+    #[test]
+    fn over_closed_input_is_negative() {
+        assert_eq!(open_delimiters("}"), -1);
+        assert_eq!(open_delimiters("print (1));"), -1);
+    }
+
+    #[test]
+    fn delimiters_in_strings_and_comments_are_ignored() {
+        assert_eq!(open_delimiters("print \"({[\";"), 0);
+        assert_eq!(open_delimiters("print 1; // {"), 0);
+        assert_eq!(open_delimiters("/* ( */ print 1;"), 0);
+        assert_eq!(open_delimiters("/* /* nested */ ( */ {"), 1);
+    }
+
+    #[test]
+    fn unclosed_strings_and_comments_are_open() {
+        assert_eq!(open_delimiters("print \"abc"), 1);
+        assert_eq!(open_delimiters("{ print \"abc"), 2);
+        assert_eq!(open_delimiters("/* comment"), 1);
+        assert_eq!(open_delimiters("/* /* nested */"), 1);
+    }
+
+    #[test]
+    fn escapes_do_not_end_strings() {
+        assert_eq!(open_delimiters(r#"print "a\"b";"#), 0);
+        assert_eq!(open_delimiters(r#"print "a\\";"#), 0);
+        assert_eq!(open_delimiters(r#"print "a\";"#), 1);
+    }
+
+    #[test]
+    fn raw_strings_have_no_escapes() {
+        assert_eq!(open_delimiters(r#"print r"a\";"#), 0);
+        assert_eq!(open_delimiters(r#"print r"a\"#), 1);
+        // Only a lone `r` starts a raw string, these are identifiers followed by a string
+        assert_eq!(open_delimiters(r#"print bar"a\";"#), 1);
+        assert_eq!(open_delimiters(r#"print r2"a\"b";"#), 0);
+    }
+
+    #[test]
+    fn triple_quoted_strings_end_at_three_quotes() {
+        assert_eq!(open_delimiters("print \"\"\"\n{ \" \"\" (\n\"\"\";"), 0);
+        assert_eq!(open_delimiters("print \"\"\"\nstill open \""), 1);
+        assert_eq!(open_delimiters("print r\"\"\"\\\"\"\";"), 0);
+        assert_eq!(open_delimiters("print \"\";"), 0);
+    }
+
+    #[test]
+    fn interpolations_are_code() {
+        assert_eq!(open_delimiters(r#"print "a ${b} c";"#), 0);
+        assert_eq!(open_delimiters(r#"print "a ${f("}")} c";"#), 0);
+        assert_eq!(open_delimiters(r#"print "a ${ {"k": 1}["k"] }";"#), 0);
+        assert_eq!(open_delimiters(r#"print "a ${f("#), 2);
+        assert_eq!(open_delimiters(r#"print "a ${b} c"#), 1);
+    }
+}
@@ -1,5 +1,6 @@
 // use crate::expr::{ExpressionStmt, Grouping, Literal, Print, Stmt, Unary};
 use crate::expr::*;
+use crate::span::Span;
 use crate::utils::errors::InterpreterError;
 use crate::{
     expr::{Binary, Expr},
@@ -14,7 +15,10 @@ use crate::{RoxyType, TryConversion};
 // declaration    → funDecl
 //                | varDecl
 //                | classDecl
+//                | importDecl
 //                | statement ;
+// importDecl     → "import" STRING "as" IDENTIFIER ";"
+//                | "from" STRING "import" IDENTIFIER ( "," IDENTIFIER )* ";" ;
 // classDecl      → "class" IDENTIFIER ( "<" IDENTIFIER )? "{" function* "}" ;
 // funDecl        → "fun" function ;
 // varDecl        → "var" IDENTIFIER ( "=" expression )? ";" ;
@@ -26,8 +30,16 @@ use crate::{RoxyType, TryConversion};
 //                | whileStmt
 //                | printStmt
 //                | returnStmt
+//                | breakStmt
+//                | continueStmt
+//                | throwStmt
+//                | tryStmt
 //                | block ;
 // returnStmt     → "return" expression? ";" ;
+// breakStmt      → "break" ";" ;
+// continueStmt   → "continue" ";" ;
+// throwStmt      → "throw" expression ";" ;
+// tryStmt        → "try" block ( "catch" "(" IDENTIFIER ")" block )? ( "finally" block )? ;
 // forStmt        → "for" "(" ( varDecl | exprStmt | ";" )
 //                expression? ";"
 //                expression? ")" statement ;
@@ -39,6 +51,7 @@ use crate::{RoxyType, TryConversion};
 // printStmt      → "print" expression ";" ;
 // expression     → assignment ;
 // assignment     → ( call "." )? IDENTIFIER "=" assignment
+//                | call "[" expression "]" "=" assignment
 //                | logic_or ;
 // logic_or       → logic_and ( "or" logic_and )* ;
 // logic_and      → equality ( "and" equality )* ;
@@ -47,11 +60,18 @@ use crate::{RoxyType, TryConversion};
 // term           → factor ( ( "-" | "+" ) factor )* ;
 // factor         → unary ( ( "/" | "*" ) unary )* ;
 // unary          → ( "!" | "-" ) unary | call ;
-// call           → primary ( "(" arguments? ")" | "." IDENTIFIER )* ;
+// call           → primary ( "(" arguments? ")" | "." IDENTIFIER | "[" expression "]" )* ;
 // arguments      → expression ( "," expression )* ;
 // primary        →  "true" | "false" | "nil" | "this"
 //                  | NUMBER | STRING | IDENTIFIER | "(" expression ")" ;
-//                  | "super" "." IDENTIFIER ;
+//                  | "super" "." IDENTIFIER
+//                  | lambda | arrow | list | map | interpolation ;
+// interpolation  → INTERPOLATION expression ( INTERPOLATION expression )* STRING ;
+// list           → "[" ( expression ( "," expression )* )? "]" ;
+// map            → "{" ( entry ( "," entry )* )? "}" ;
+// entry          → expression ":" expression ;
+// lambda         → "fun" "(" parameters? ")" block ;
+// arrow          → "(" parameters? ")" "=>" ( expression | block ) ;
 
 // TODO: Think about how to add this too
 //, -> C
@@ -77,26 +97,33 @@ impl Parser {
         }
     }
 
+    // Panic mode recovery: discard tokens until we are probably at the start of the next statement,
+    // i.e. right after a ';' or right before a keyword which begins a statement
     pub fn synchronize(&mut self, token: &Token) -> Result<(), RoxyError> {
         self.advance(token)?;
 
         while !self.is_at_end() {
             match self.previous() {
                 Some(token) => {
-                    if token.token_type == TokenType::Semicolon {
+                    if token.token_type == TokenType::Semicolon || self.is_from_import() {
                         return Ok(());
                     }
 
                     match self.peek() {
                         Some(token) => match token.token_type {
-                            TokenType::Class => todo!(),
-                            TokenType::Fun => todo!(),
-                            TokenType::Var => todo!(),
-                            TokenType::For => todo!(),
-                            TokenType::If => todo!(),
-                            TokenType::While => todo!(),
-                            TokenType::Print => todo!(),
-                            TokenType::Return => todo!(),
+                            TokenType::Class
+                            | TokenType::Fun
+                            | TokenType::Var
+                            | TokenType::For
+                            | TokenType::If
+                            | TokenType::While
+                            | TokenType::Print
+                            | TokenType::Return
+                            | TokenType::Break
+                            | TokenType::Continue
+                            | TokenType::Import
+                            | TokenType::Throw
+                            | TokenType::Try => return Ok(()),
                             _ => (),
                         },
                         None => {
@@ -119,52 +146,47 @@ impl Parser {
         Ok(())
     }
 
-    // This has 3 cases:
-    // - Ok is returned containing Some(expr), that means parsing is successful
-    // - Ok is returned containing None, that means parsing is not successful but no critical errors
-    // were reported so check errors field of parser, to get a list of parsing errors
+    // This has 2 cases:
+    // - Ok is returned containing all the statements which could be parsed, if errors field of parser
+    // is not empty then parsing was not successful and the statements only form a partial AST
     // - Error is returned meaning there is some critical error
-    pub fn parse(&mut self) -> Result<Option<Vec<Stmt>>, RoxyError> {
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, RoxyError> {
         let mut stmts = vec![];
         while !self.is_at_end() {
-            match self.declaration() {
-                Ok(stmt) => {
-                    stmts.push(stmt);
-                }
-                Err(err) => {
-                    //TODO: See how to get this working
-                    // self.synchronize(token);
-                    match err {
-                        RoxyError::ParserError(ref parser_error) => match parser_error {
-                            ParserError::InvalidPeek => return Err(err),
-                            _ => self.errors.push(err),
-                        },
-                        _ => unreachable!(),
-                    };
-
-                    return Ok(None);
-                }
+            if let Some(stmt) = self.declaration()? {
+                stmts.push(stmt);
             }
         }
 
-        Ok(Some(stmts))
+        Ok(stmts)
     }
 
+    // Records a recoverable error, critical errors are handed back to be propagated
+    fn report(&mut self, err: RoxyError) -> Result<(), RoxyError> {
+        match err {
+            RoxyError::ParserError(ParserError::InvalidPeek) => Err(err),
+            _ => {
+                self.errors.push(err);
+                Ok(())
+            }
+        }
+    }
+
+    // Parses the tokens as a single expression, used by the prompt to echo values. None is returned
+    // when the tokens do not form an expression or there are tokens left after it, in that case
+    // the caller should parse them as statements instead
     pub fn parse_expression(&mut self) -> Result<Option<Expr>, RoxyError> {
-        while !self.is_at_end() {
+        if !self.is_at_end() {
             match self.expression() {
                 Ok((_, expr)) => {
+                    if !self.is_at_end() {
+                        return Ok(None);
+                    }
+
                     return Ok(Some(expr));
                 }
                 Err(err) => {
-                    match err {
-                        RoxyError::ParserError(ref parser_error) => match parser_error {
-                            ParserError::InvalidPeek => return Err(err),
-                            _ => self.errors.push(err),
-                        },
-                        _ => unreachable!(),
-                    };
-
+                    self.report(err)?;
                     return Ok(None);
                 }
             }
@@ -175,7 +197,7 @@ impl Parser {
 
     pub fn left_recursive_parsing<F>(
         &mut self,
-        token_types: &Vec<TokenType>,
+        token_types: &[TokenType],
         rule_fn: F,
         // rule_fn: &mut dyn FnMut() -> Result<Expr, LoxError>,
         expr_type: ExprType,
@@ -188,7 +210,7 @@ impl Parser {
         last_visited_token = visited_token;
 
         loop {
-            let (visited_token, matched) = self.does_any_token_type_match(&token_types)?;
+            let (visited_token, matched) = self.does_any_token_type_match(token_types)?;
             if !matched {
                 break;
             }
@@ -203,13 +225,18 @@ impl Parser {
 
                     last_visited_token = visited_token;
 
+                    let span = expr.span().merge(&right.span());
                     expr = match expr_type {
                         ExprType::Binary => Expr::Binary(Binary {
+                            id: next_expr_id(),
+                            span,
                             left: Box::new(expr),
                             operator,
                             right: Box::new(right),
                         }),
                         ExprType::Logical => Expr::Logical(Logical {
+                            id: next_expr_id(),
+                            span,
                             left: Box::new(expr),
                             operator,
                             right: Box::new(right),
@@ -227,26 +254,59 @@ impl Parser {
         Ok((last_visited_token, expr))
     }
 
-    fn declaration(&mut self) -> Result<Stmt, RoxyError> {
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::Class])?;
+    // Errors are recorded and recovered from here, so a single bad statement neither stops the
+    // parse nor hides the errors after it. None is returned for the statement which failed
+    fn declaration(&mut self) -> Result<Option<Stmt>, RoxyError> {
+        match self.declaration_stmt() {
+            Ok(stmt) => Ok(Some(stmt)),
+            Err(err) => {
+                self.report(err)?;
+                let (token, _) = self.check(&TokenType::EOF)?;
+                self.synchronize(&token)?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn declaration_stmt(&mut self) -> Result<Stmt, RoxyError> {
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Class])?;
         if matched {
             return self.class_decl(visited_token, "class".into());
         }
 
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::Fun])?;
-        if matched {
-            return self.function(visited_token, String::from("function"));
+        // `fun (` starts an anonymous function used as an expression statement
+        let is_lambda = self
+            .tokens
+            .get(self.current + 1)
+            .is_some_and(|token| token.token_type == TokenType::LeftParen);
+        if !is_lambda {
+            let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Fun])?;
+            if matched {
+                return self.function(visited_token, String::from("function"));
+            }
         }
 
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::Var])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Var])?;
         if matched {
             return self.var_decl(visited_token);
         }
 
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Import])?;
+        if matched {
+            return self.import_decl(visited_token);
+        }
+
+        if self.is_from_import() {
+            let (token, _) = self.check(&TokenType::Identifier)?;
+            let keyword = self.advance(&token)?;
+            return self.import_decl(keyword);
+        }
+
         return self.statement();
     }
 
     fn class_decl(&mut self, token: Token, kind: String) -> Result<Stmt, RoxyError> {
+        let start = token.span;
         let mut last_visited_token = token;
 
         let name = self.consume(
@@ -259,7 +319,7 @@ impl Parser {
         )?;
 
         let mut superclass = None;
-        let (token, matched) = self.does_any_token_type_match(&vec![TokenType::Less])?;
+        let (token, matched) = self.does_any_token_type_match(&[TokenType::Less])?;
         last_visited_token = token;
         if matched {
             let superclass_name = self.consume(
@@ -270,6 +330,8 @@ impl Parser {
             )?;
 
             superclass = Some(Variable {
+                id: next_expr_id(),
+                span: superclass_name.span,
                 name: superclass_name.clone(),
             });
         }
@@ -297,7 +359,7 @@ impl Parser {
             }
         }
 
-        self.consume(
+        let right_brace = self.consume(
             &TokenType::RightBrace,
             RoxyError::ParserError(ParserError::ExpectedRightBraceAfterBlock(
                 last_visited_token,
@@ -305,6 +367,7 @@ impl Parser {
         )?;
 
         return Ok(Stmt::Class(Class {
+            span: start.merge(&right_brace.span),
             name,
             methods,
             superclass,
@@ -312,7 +375,8 @@ impl Parser {
     }
 
     fn function(&mut self, token: Token, kind: String) -> Result<Stmt, RoxyError> {
-        let mut last_visited_token = token;
+        let start = token.span;
+        let last_visited_token = token;
         let name = self.consume(
             &TokenType::Identifier,
             RoxyError::ParserError(ParserError::ExpectedIdentifier(
@@ -331,9 +395,31 @@ impl Parser {
             )),
         )?;
 
+        let (last_visited_token, params) = self.parameters()?;
+
+        self.consume(
+            &TokenType::LeftBrace,
+            RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                "{".into(),
+                "function name".into(),
+                last_visited_token,
+            )),
+        )?;
+
+        let body = self.block()?;
+
+        Ok(Stmt::Function(Function {
+            span: start.merge(&self.previous_span()),
+            name,
+            params,
+            body,
+        }))
+    }
+
+    // Parameter list of a function, the `(` has already been consumed
+    fn parameters(&mut self) -> Result<(Token, Vec<Token>), RoxyError> {
         let mut params = vec![];
-        let (visited_token, matched) = self.check(&TokenType::RightParen)?;
-        last_visited_token = visited_token;
+        let (mut last_visited_token, matched) = self.check(&TokenType::RightParen)?;
         if !matched {
             loop {
                 if params.len() > 255 {
@@ -350,7 +436,7 @@ impl Parser {
                 params.push(token);
 
                 let (visited_token, matched) =
-                    self.does_any_token_type_match(&vec![TokenType::Comma])?;
+                    self.does_any_token_type_match(&[TokenType::Comma])?;
                 last_visited_token = visited_token;
                 if !matched {
                     break;
@@ -363,75 +449,395 @@ impl Parser {
             RoxyError::ParserError(ParserError::ExpectedRightParen(last_visited_token.clone())),
         )?;
 
+        return Ok((last_visited_token, params));
+    }
+
+    // `fun (a, b) { ... }`, the `fun` keyword has already been consumed
+    fn lambda(&mut self, keyword: Token) -> Result<(Token, Expr), RoxyError> {
+        self.consume(
+            &TokenType::LeftParen,
+            RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                "(".into(),
+                "fun".into(),
+                keyword.clone(),
+            )),
+        )?;
+
+        let (last_visited_token, params) = self.parameters()?;
+
         self.consume(
             &TokenType::LeftBrace,
             RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
                 "{".into(),
-                "function name".into(),
+                "function parameters".into(),
                 last_visited_token,
             )),
         )?;
 
         let body = self.block()?;
+        let right_brace = self.previous_span();
 
-        Ok(Stmt::Function(Function { name, params, body }))
+        return Ok((
+            self.previous().unwrap_or(keyword.clone()),
+            Expr::Lambda(Lambda {
+                id: next_expr_id(),
+                span: keyword.span.merge(&right_brace),
+                keyword,
+                params,
+                body,
+            }),
+        ));
+    }
+
+    // `(a, b) => a + b` or `(a, b) => { ... }`, the `(` has already been consumed
+    fn arrow_function(&mut self, left_paren: Token) -> Result<(Token, Expr), RoxyError> {
+        let (last_visited_token, params) = self.parameters()?;
+
+        let arrow = self.consume(
+            &TokenType::Arrow,
+            RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                "=>".into(),
+                "arrow function parameters".into(),
+                last_visited_token,
+            )),
+        )?;
+
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::LeftBrace])?;
+        let (last_visited_token, body) = if matched {
+            let body = self.block()?;
+            (self.previous().unwrap_or(visited_token), body)
+        } else {
+            let (last_visited_token, value) = self.expression()?;
+            let body = vec![Stmt::Return(Return {
+                span: value.span(),
+                keyword: arrow.clone(),
+                value: Some(value),
+            })];
+            (last_visited_token, body)
+        };
+
+        return Ok((
+            last_visited_token,
+            Expr::Lambda(Lambda {
+                id: next_expr_id(),
+                span: left_paren.span.merge(&self.previous_span()),
+                keyword: arrow,
+                params,
+                body,
+            }),
+        ));
+    }
+
+    // `[a, b, c]`, the `[` has already been consumed
+    fn list(&mut self, left_bracket: Token) -> Result<(Token, Expr), RoxyError> {
+        let mut elements = vec![];
+        let (mut last_visited_token, matched) = self.check(&TokenType::RightBracket)?;
+        if !matched {
+            loop {
+                let (_, element) = self.expression()?;
+                elements.push(element);
+
+                let (visited_token, matched) =
+                    self.does_any_token_type_match(&[TokenType::Comma])?;
+                last_visited_token = visited_token;
+                if !matched {
+                    break;
+                }
+            }
+        }
+
+        let right_bracket = self.consume(
+            &TokenType::RightBracket,
+            RoxyError::ParserError(ParserError::ExpectedRightBracket(last_visited_token)),
+        )?;
+
+        return Ok((
+            right_bracket.clone(),
+            Expr::List(List {
+                id: next_expr_id(),
+                span: left_bracket.span.merge(&right_bracket.span),
+                elements,
+            }),
+        ));
+    }
+
+    // `"a ${b} c"`, the scanner hands over the pieces of text around the tokens of each
+    // expression. The first piece has already been consumed
+    fn interpolation(&mut self, head: Token) -> Result<(Token, Expr), RoxyError> {
+        let mut parts = vec![];
+        let mut segment = head.clone();
+        loop {
+            if !segment.literal.to_string().is_empty() {
+                parts.push(Expr::Literal(Literal {
+                    id: next_expr_id(),
+                    span: segment.span,
+                    value: segment.literal.clone(),
+                }));
+            }
+
+            let (last_visited_token, expr) = self.expression()?;
+            parts.push(expr);
+
+            let (token, matched) = self.does_any_token_type_match(&[TokenType::Interpolation])?;
+            if matched {
+                segment = token;
+                continue;
+            }
+
+            let tail = self.consume(
+                &TokenType::String,
+                RoxyError::ParserError(ParserError::ExpectedInterpolationEnd(last_visited_token)),
+            )?;
+            if !tail.literal.to_string().is_empty() {
+                parts.push(Expr::Literal(Literal {
+                    id: next_expr_id(),
+                    span: tail.span,
+                    value: tail.literal.clone(),
+                }));
+            }
+
+            return Ok((
+                tail.clone(),
+                Expr::Interpolation(Interpolation {
+                    id: next_expr_id(),
+                    span: head.span.merge(&tail.span),
+                    parts,
+                }),
+            ));
+        }
+    }
+
+    // `{"a": 1, "b": 2}`, the `{` has already been consumed
+    fn map(&mut self, left_brace: Token) -> Result<(Token, Expr), RoxyError> {
+        let mut entries = vec![];
+        let (mut last_visited_token, matched) = self.check(&TokenType::RightBrace)?;
+        if !matched {
+            loop {
+                let (visited_token, key) = self.expression()?;
+                self.consume(
+                    &TokenType::Colon,
+                    RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                        ":".into(),
+                        "map key".into(),
+                        visited_token,
+                    )),
+                )?;
+                let (_, value) = self.expression()?;
+                entries.push((key, value));
+
+                let (visited_token, matched) =
+                    self.does_any_token_type_match(&[TokenType::Comma])?;
+                last_visited_token = visited_token;
+                if !matched {
+                    break;
+                }
+            }
+        }
+
+        let right_brace = self.consume(
+            &TokenType::RightBrace,
+            RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                "}".into(),
+                "map entries".into(),
+                last_visited_token,
+            )),
+        )?;
+
+        return Ok((
+            right_brace.clone(),
+            Expr::Map(Map {
+                id: next_expr_id(),
+                span: left_brace.span.merge(&right_brace.span),
+                brace: right_brace,
+                entries,
+            }),
+        ));
+    }
+
+    // Whether the tokens after a `(` are an arrow function's parameters, i.e.
+    // `IDENTIFIER ( "," IDENTIFIER )* ")" "=>"` or `")" "=>"`
+    fn is_arrow_function(&self) -> bool {
+        let mut i = self.current;
+        while let Some(token) = self.tokens.get(i) {
+            match token.token_type {
+                TokenType::Identifier | TokenType::Comma => i += 1,
+                TokenType::RightParen => {
+                    return self
+                        .tokens
+                        .get(i + 1)
+                        .is_some_and(|token| token.token_type == TokenType::Arrow);
+                }
+                _ => return false,
+            }
+        }
+
+        return false;
     }
 
     fn var_decl(&mut self, token: Token) -> Result<Stmt, RoxyError> {
+        let start = token.span;
         let name = self.consume(
             &TokenType::Identifier,
             RoxyError::ParserError(ParserError::ExpectedVariableName(token)),
         )?;
 
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::Equal])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Equal])?;
         let mut initializer: Option<Expr> = None;
         if matched {
-            if let Ok((_, expr)) = self.expression() {
-                initializer = Some(expr);
-            }
+            let (_, expr) = self.expression()?;
+            initializer = Some(expr);
         }
 
-        self.consume(
+        let semicolon = self.consume(
             &TokenType::Semicolon,
             RoxyError::ParserError(ParserError::ExpectedSemicolon(visited_token)),
         )?;
 
         return Ok(Stmt::VariableStmt(VariableStmt {
+            span: start.merge(&semicolon.span),
             name,
             value: initializer,
         }));
     }
 
+    // Both `import "path.rx" as name;` and `from "path.rx" import a, b;`
+    fn import_decl(&mut self, keyword: Token) -> Result<Stmt, RoxyError> {
+        let path = self.consume(
+            &TokenType::String,
+            RoxyError::ParserError(ParserError::ExpectedModulePath(keyword.clone())),
+        )?;
+
+        let mut alias = None;
+        let mut names = vec![];
+        if keyword.token_type == TokenType::Import {
+            if !self.is_contextual_keyword(0, "as") {
+                return Err(RoxyError::ParserError(
+                    ParserError::ExpectedPunctAfterKeyword(
+                        "as".into(),
+                        "module path".into(),
+                        path.clone(),
+                    ),
+                ));
+            }
+            self.advance(&path)?;
+
+            alias = Some(self.consume(
+                &TokenType::Identifier,
+                RoxyError::ParserError(ParserError::ExpectedIdentifier(
+                    "module name".into(),
+                    "after `as`".into(),
+                    path.clone(),
+                )),
+            )?);
+        } else {
+            let mut last_visited_token = self.consume(
+                &TokenType::Import,
+                RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                    "import".into(),
+                    "module path".into(),
+                    path.clone(),
+                )),
+            )?;
+
+            loop {
+                let name = self.consume(
+                    &TokenType::Identifier,
+                    RoxyError::ParserError(ParserError::ExpectedIdentifier(
+                        "imported name".into(),
+                        "after `import`".into(),
+                        last_visited_token,
+                    )),
+                )?;
+                names.push(name);
+
+                let (visited_token, matched) =
+                    self.does_any_token_type_match(&[TokenType::Comma])?;
+                last_visited_token = visited_token;
+                if !matched {
+                    break;
+                }
+            }
+        }
+
+        let (visited_token, _) = self.check(&TokenType::Semicolon)?;
+        let semicolon = self.consume(
+            &TokenType::Semicolon,
+            RoxyError::ParserError(ParserError::ExpectedSemicolon(visited_token)),
+        )?;
+
+        return Ok(Stmt::Import(Import {
+            span: keyword.span.merge(&semicolon.span),
+            keyword,
+            path,
+            alias,
+            names,
+        }));
+    }
+
+    // `as` and `from` are only keywords inside imports, everywhere else they are ordinary names
+    fn is_contextual_keyword(&self, offset: usize, word: &str) -> bool {
+        return self.tokens.get(self.current + offset).is_some_and(|token| {
+            token.token_type == TokenType::Identifier && token.lexeme == word
+        });
+    }
+
+    // A variable called `from` is never followed by a string
+    fn is_from_import(&self) -> bool {
+        return self.is_contextual_keyword(0, "from")
+            && self
+                .tokens
+                .get(self.current + 1)
+                .is_some_and(|token| token.token_type == TokenType::String);
+    }
+
     fn statement(&mut self) -> Result<Stmt, RoxyError> {
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::For])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::For])?;
         if matched {
             return self.for_stmt(visited_token);
         }
 
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::If])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::If])?;
         if matched {
             return self.if_stmt(visited_token);
         }
 
-        let (_, matched) = self.does_any_token_type_match(&vec![TokenType::Print])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Print])?;
         if matched {
-            return self.print_stmt();
+            return self.print_stmt(visited_token);
         }
 
-        let (_, matched) = self.does_any_token_type_match(&vec![TokenType::Return])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Return])?;
         if matched {
             return self.return_stmt(visited_token);
         }
 
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::While])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::While])?;
         if matched {
             return self.while_stmt(visited_token);
         }
 
-        let (_, matched) = self.does_any_token_type_match(&vec![TokenType::LeftBrace])?;
+        let (visited_token, matched) =
+            self.does_any_token_type_match(&[TokenType::Break, TokenType::Continue])?;
+        if matched {
+            return self.loop_control_stmt(visited_token);
+        }
+
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Throw])?;
+        if matched {
+            return self.throw_stmt(visited_token);
+        }
+
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Try])?;
         if matched {
+            return self.try_stmt(visited_token);
+        }
+
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::LeftBrace])?;
+        if matched {
+            let statements = self.block()?;
             return Ok(Stmt::Block(Block {
-                statements: self.block()?,
+                span: visited_token.span.merge(&self.previous_span()),
+                statements,
             }));
         }
 
@@ -450,12 +856,125 @@ impl Parser {
             value = Some(expr);
         }
 
-        self.consume(
+        let semicolon = self.consume(
             &TokenType::Semicolon,
             RoxyError::ParserError(ParserError::ExpectedSemicolon(last_visited_token)),
         )?;
 
-        return Ok(Stmt::Return(Return { keyword, value }));
+        return Ok(Stmt::Return(Return {
+            span: keyword.span.merge(&semicolon.span),
+            keyword,
+            value,
+        }));
+    }
+
+    // `break;` and `continue;`, the resolver makes sure they are inside a loop
+    fn loop_control_stmt(&mut self, keyword: Token) -> Result<Stmt, RoxyError> {
+        let semicolon = self.consume(
+            &TokenType::Semicolon,
+            RoxyError::ParserError(ParserError::ExpectedSemicolon(keyword.clone())),
+        )?;
+        let span = keyword.span.merge(&semicolon.span);
+
+        if keyword.token_type == TokenType::Break {
+            return Ok(Stmt::Break(Break { span, keyword }));
+        }
+
+        return Ok(Stmt::Continue(Continue { span, keyword }));
+    }
+
+    fn throw_stmt(&mut self, keyword: Token) -> Result<Stmt, RoxyError> {
+        let (last_visited_token, value) = self.expression()?;
+        let semicolon = self.consume(
+            &TokenType::Semicolon,
+            RoxyError::ParserError(ParserError::ExpectedSemicolon(last_visited_token)),
+        )?;
+
+        return Ok(Stmt::Throw(Throw {
+            span: keyword.span.merge(&semicolon.span),
+            keyword,
+            value,
+        }));
+    }
+
+    fn try_stmt(&mut self, keyword: Token) -> Result<Stmt, RoxyError> {
+        self.consume(
+            &TokenType::LeftBrace,
+            RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                "{".into(),
+                "try".into(),
+                keyword.clone(),
+            )),
+        )?;
+        let body = self.block()?;
+
+        let mut catch = None;
+        let (catch_keyword, matched) = self.does_any_token_type_match(&[TokenType::Catch])?;
+        if matched {
+            self.consume(
+                &TokenType::LeftParen,
+                RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                    "(".into(),
+                    "catch".into(),
+                    catch_keyword.clone(),
+                )),
+            )?;
+            let name = self.consume(
+                &TokenType::Identifier,
+                RoxyError::ParserError(ParserError::ExpectedIdentifier(
+                    "caught error".into(),
+                    "after `catch`".into(),
+                    catch_keyword,
+                )),
+            )?;
+            self.consume(
+                &TokenType::RightParen,
+                RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                    ")".into(),
+                    "catch variable".into(),
+                    name.clone(),
+                )),
+            )?;
+            self.consume(
+                &TokenType::LeftBrace,
+                RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                    "{".into(),
+                    "catch".into(),
+                    name.clone(),
+                )),
+            )?;
+            let body = self.block()?;
+            catch = Some(Catch { name, body });
+        }
+
+        let mut finally = None;
+        let (finally_keyword, matched) = self.does_any_token_type_match(&[TokenType::Finally])?;
+        if matched {
+            self.consume(
+                &TokenType::LeftBrace,
+                RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
+                    "{".into(),
+                    "finally".into(),
+                    finally_keyword,
+                )),
+            )?;
+            finally = Some(self.block()?);
+        }
+
+        let end = self.previous().unwrap_or(keyword.clone());
+        if catch.is_none() && finally.is_none() {
+            return Err(RoxyError::ParserError(ParserError::ExpectedCatchOrFinally(
+                end,
+            )));
+        }
+
+        return Ok(Stmt::Try(Try {
+            span: keyword.span.merge(&end.span),
+            keyword,
+            body,
+            catch,
+            finally,
+        }));
     }
 
     fn for_stmt(&mut self, token: Token) -> Result<Stmt, RoxyError> {
@@ -469,11 +988,11 @@ impl Parser {
         )?;
 
         let initializer_opt;
-        let (_, matched) = self.does_any_token_type_match(&vec![TokenType::Semicolon])?;
+        let (_, matched) = self.does_any_token_type_match(&[TokenType::Semicolon])?;
         if matched {
             initializer_opt = None;
         } else {
-            let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::Var])?;
+            let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Var])?;
             if matched {
                 initializer_opt = Some(self.var_decl(visited_token)?);
             } else {
@@ -510,34 +1029,32 @@ impl Parser {
         )?;
 
         let mut body = self.statement()?;
-
-        if let Some(increment) = increment_opt {
-            body = Stmt::Block(Block {
-                statements: vec![
-                    body,
-                    Stmt::Expression(ExpressionStmt {
-                        expression: increment,
-                    }),
-                ],
-            });
-        }
+        // All the desugared nodes cover the whole for statement
+        let span = token.span.merge(&body.span());
 
         let condition: Expr;
         if let Some(cond) = condition_opt {
             condition = cond;
         } else {
             condition = Expr::Literal(Literal {
+                id: next_expr_id(),
+                span: token.span,
                 value: RoxyType::Boolean(true),
             });
         }
 
+        // The increment is kept on the loop instead of being appended to the body, so that a
+        // `continue` in the body does not skip it
         body = Stmt::While(While {
+            span,
             condition,
             body: Box::new(body),
+            increment: increment_opt.map(Box::new),
         });
 
         if let Some(initializer) = initializer_opt {
             body = Stmt::Block(Block {
+                span,
                 statements: vec![initializer, body],
             });
         }
@@ -546,6 +1063,7 @@ impl Parser {
     }
 
     fn while_stmt(&mut self, token: Token) -> Result<Stmt, RoxyError> {
+        let start = token.span;
         self.consume(
             &TokenType::LeftParen,
             RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
@@ -569,12 +1087,15 @@ impl Parser {
         let body = self.statement()?;
 
         Ok(Stmt::While(While {
+            span: start.merge(&body.span()),
             condition,
             body: Box::new(body),
+            increment: None,
         }))
     }
 
     fn if_stmt(&mut self, token: Token) -> Result<Stmt, RoxyError> {
+        let start = token.span;
         self.consume(
             &TokenType::LeftParen,
             RoxyError::ParserError(ParserError::ExpectedPunctAfterKeyword(
@@ -599,12 +1120,13 @@ impl Parser {
         let then_branch = self.statement()?;
 
         let mut else_branch: Option<Box<Stmt>> = None;
-        let (_, matched) = self.does_any_token_type_match(&vec![TokenType::Else])?;
+        let (_, matched) = self.does_any_token_type_match(&[TokenType::Else])?;
         if matched {
             else_branch = Some(Box::new(self.statement()?));
         }
 
         return Ok(Stmt::If(If {
+            span: start.merge(&self.previous_span()),
             condition,
             then_branch: Box::new(then_branch),
             else_branch,
@@ -622,7 +1144,9 @@ impl Parser {
                 break;
             }
 
-            stmts.push(self.declaration()?);
+            if let Some(stmt) = self.declaration()? {
+                stmts.push(stmt);
+            }
         }
 
         self.consume(
@@ -632,22 +1156,28 @@ impl Parser {
         return Ok(stmts);
     }
 
-    fn print_stmt(&mut self) -> Result<Stmt, RoxyError> {
+    fn print_stmt(&mut self, token: Token) -> Result<Stmt, RoxyError> {
         let (last_visited_token, expr) = self.expression()?;
-        self.consume(
+        let semicolon = self.consume(
             &TokenType::Semicolon,
             RoxyError::ParserError(ParserError::ExpectedSemicolon(last_visited_token.clone())),
         )?;
-        return Ok(Stmt::Print(Print { expression: expr }));
+        return Ok(Stmt::Print(Print {
+            span: token.span.merge(&semicolon.span),
+            expression: expr,
+        }));
     }
 
     fn expr_stmt(&mut self) -> Result<Stmt, RoxyError> {
         let (last_visited_token, expr) = self.expression()?;
-        self.consume(
+        let semicolon = self.consume(
             &TokenType::Semicolon,
             RoxyError::ParserError(ParserError::ExpectedSemicolon(last_visited_token.clone())),
         )?;
-        return Ok(Stmt::Expression(ExpressionStmt { expression: expr }));
+        return Ok(Stmt::Expression(ExpressionStmt {
+            span: expr.span().merge(&semicolon.span),
+            expression: expr,
+        }));
     }
 
     fn expression(&mut self) -> Result<(Token, Expr), RoxyError> {
@@ -658,7 +1188,7 @@ impl Parser {
         let mut last_visited_token: Token;
         let (_, expr) = self.or()?;
 
-        let (visited_token, matched) = self.does_any_token_type_match(&vec![TokenType::Equal])?;
+        let (visited_token, matched) = self.does_any_token_type_match(&[TokenType::Equal])?;
         last_visited_token = visited_token;
 
         if matched {
@@ -674,6 +1204,8 @@ impl Parser {
                             return Ok((
                                 last_visited_token,
                                 Expr::Assign(Assign {
+                                    id: next_expr_id(),
+                                    span: variable.span.merge(&value.span()),
                                     name,
                                     value: Box::new(value),
                                 }),
@@ -683,12 +1215,27 @@ impl Parser {
                             return Ok((
                                 last_visited_token,
                                 Expr::Set(Set {
+                                    id: next_expr_id(),
+                                    span: get.span.merge(&value.span()),
                                     object: get.object,
                                     name: get.name,
                                     value: Box::new(value),
                                 }),
                             ));
                         }
+                        Expr::Index(index) => {
+                            return Ok((
+                                last_visited_token,
+                                Expr::IndexSet(IndexSet {
+                                    id: next_expr_id(),
+                                    span: index.span.merge(&value.span()),
+                                    object: index.object,
+                                    bracket: index.bracket,
+                                    index: index.index,
+                                    value: Box::new(value),
+                                }),
+                            ));
+                        }
                         _ => {
                             return Err(RoxyError::ParserError(
                                 ParserError::InvalidAssignmentTarget(equals),
@@ -708,16 +1255,16 @@ impl Parser {
     }
 
     fn or(&mut self) -> Result<(Token, Expr), RoxyError> {
-        self.left_recursive_parsing(&vec![TokenType::Or], Parser::and, ExprType::Logical)
+        self.left_recursive_parsing(&[TokenType::Or], Parser::and, ExprType::Logical)
     }
 
     fn and(&mut self) -> Result<(Token, Expr), RoxyError> {
-        self.left_recursive_parsing(&vec![TokenType::And], Parser::equality, ExprType::Logical)
+        self.left_recursive_parsing(&[TokenType::And], Parser::equality, ExprType::Logical)
     }
 
     fn equality(&mut self) -> Result<(Token, Expr), RoxyError> {
         return self.left_recursive_parsing(
-            &vec![TokenType::BangEqual, TokenType::EqualEqual],
+            &[TokenType::BangEqual, TokenType::EqualEqual],
             Parser::comparison,
             ExprType::Binary,
         );
@@ -725,7 +1272,7 @@ impl Parser {
 
     pub fn comparison(&mut self) -> Result<(Token, Expr), RoxyError> {
         return self.left_recursive_parsing(
-            &vec![
+            &[
                 TokenType::Greater,
                 TokenType::GreaterEqual,
                 TokenType::Less,
@@ -738,7 +1285,7 @@ impl Parser {
 
     pub fn term(&mut self) -> Result<(Token, Expr), RoxyError> {
         return self.left_recursive_parsing(
-            &vec![TokenType::Minus, TokenType::Plus],
+            &[TokenType::Minus, TokenType::Plus],
             Parser::factor,
             ExprType::Binary,
         );
@@ -746,7 +1293,7 @@ impl Parser {
 
     pub fn factor(&mut self) -> Result<(Token, Expr), RoxyError> {
         return self.left_recursive_parsing(
-            &vec![TokenType::Slash, TokenType::Star],
+            &[TokenType::Slash, TokenType::Star],
             Parser::unary,
             ExprType::Binary,
         );
@@ -754,7 +1301,7 @@ impl Parser {
 
     pub fn unary(&mut self) -> Result<(Token, Expr), RoxyError> {
         let (token, matched) =
-            self.does_any_token_type_match(&vec![TokenType::Bang, TokenType::Minus])?;
+            self.does_any_token_type_match(&[TokenType::Bang, TokenType::Minus])?;
 
         if matched {
             match self.previous() {
@@ -765,6 +1312,8 @@ impl Parser {
                     return Ok((
                         last_visited_token,
                         Expr::Unary(Unary {
+                            id: next_expr_id(),
+                            span: operator.span.merge(&right.span()),
                             operator,
                             right: Box::new(right),
                         }),
@@ -786,20 +1335,34 @@ impl Parser {
         let (_, mut expr) = self.primary()?;
 
         loop {
-            let (_, matched_left_paren) =
-                self.does_any_token_type_match(&vec![TokenType::LeftParen])?;
-
-            let (visited_token, matched_dot) =
-                self.does_any_token_type_match(&vec![TokenType::Dot])?;
+            let (visited_token, matched) = self.does_any_token_type_match(&[
+                TokenType::LeftParen,
+                TokenType::Dot,
+                TokenType::LeftBracket,
+            ])?;
             last_visited_token = visited_token;
-            if !matched_left_paren && !matched_dot {
+            if !matched {
                 break;
             }
 
-            if matched_left_paren {
+            if last_visited_token.token_type == TokenType::LeftParen {
                 let (_, finish_call_expr) = self.finish_call(&expr)?;
                 expr = finish_call_expr;
-            } else if matched_dot {
+            } else if last_visited_token.token_type == TokenType::LeftBracket {
+                let (visited_token, index) = self.expression()?;
+                let bracket = self.consume(
+                    &TokenType::RightBracket,
+                    RoxyError::ParserError(ParserError::ExpectedRightBracket(visited_token)),
+                )?;
+
+                expr = Expr::Index(Index {
+                    id: next_expr_id(),
+                    span: expr.span().merge(&bracket.span),
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                })
+            } else {
                 let name = self.consume(
                     &TokenType::Identifier,
                     RoxyError::ParserError(ParserError::ExpectedIdentifier(
@@ -810,6 +1373,8 @@ impl Parser {
                 )?;
 
                 expr = Expr::Get(Get {
+                    id: next_expr_id(),
+                    span: expr.span().merge(&name.span),
                     object: Box::new(expr),
                     name,
                 })
@@ -839,7 +1404,7 @@ impl Parser {
                 arguments.push(expr);
 
                 let (visited_token, matched) =
-                    self.does_any_token_type_match(&vec![TokenType::Comma])?;
+                    self.does_any_token_type_match(&[TokenType::Comma])?;
                 last_visited_token = visited_token;
 
                 if !matched {
@@ -860,6 +1425,8 @@ impl Parser {
         return Ok((
             last_visited_token,
             Expr::Call(Call {
+                id: next_expr_id(),
+                span: callee.span().merge(&paren.span),
                 callee: Box::new(callee.to_owned()),
                 paren,
                 arguments,
@@ -869,32 +1436,33 @@ impl Parser {
 
     fn primary(&mut self) -> Result<(Token, Expr), RoxyError> {
         if let (token, Some(expr)) =
-            self.match_token_types_and_create_literal(&vec![TokenType::False])?
+            self.match_token_types_and_create_literal(&[TokenType::False])?
         {
             return Ok((token, expr));
         }
 
         if let (token, Some(expr)) =
-            self.match_token_types_and_create_literal(&vec![TokenType::True])?
+            self.match_token_types_and_create_literal(&[TokenType::True])?
         {
             return Ok((token, expr));
         }
 
-        if let (token, Some(expr)) =
-            self.match_token_types_and_create_literal(&vec![TokenType::Nil])?
-        {
+        if let (token, Some(expr)) = self.match_token_types_and_create_literal(&[TokenType::Nil])? {
             return Ok((token, expr));
         }
 
         if let (token, Some(expr)) =
-            self.match_token_types_and_create_literal(&vec![TokenType::Number, TokenType::String])?
+            self.match_token_types_and_create_literal(&[TokenType::Number, TokenType::String])?
         {
             return Ok((token, expr));
         }
 
-        if let (token, Some(_)) =
-            self.match_token_types_and_create_literal(&vec![TokenType::Super])?
-        {
+        let (token, matched) = self.does_any_token_type_match(&[TokenType::Interpolation])?;
+        if matched {
+            return self.interpolation(token);
+        }
+
+        if let (token, Some(_)) = self.match_token_types_and_create_literal(&[TokenType::Super])? {
             let super_kw = token;
 
             let token = self.consume(
@@ -912,6 +1480,8 @@ impl Parser {
             return Ok((
                 method.clone(),
                 Expr::Super(Super {
+                    id: next_expr_id(),
+                    span: super_kw.span.merge(&method.span),
                     keyword: super_kw,
                     method,
                 }),
@@ -919,27 +1489,49 @@ impl Parser {
         }
 
         if let (token, Some(expr)) =
-            self.match_token_types_and_create_literal(&vec![TokenType::This])?
+            self.match_token_types_and_create_literal(&[TokenType::This])?
         {
             return Ok((token, expr));
         }
 
         if let (token, Some(expr)) =
-            self.match_token_types_and_create_literal(&vec![TokenType::Identifier])?
+            self.match_token_types_and_create_literal(&[TokenType::Identifier])?
         {
             return Ok((token, expr));
         }
 
-        let (token, matched) = self.does_any_token_type_match(&vec![TokenType::LeftParen])?;
+        let (token, matched) = self.does_any_token_type_match(&[TokenType::Fun])?;
+        if matched {
+            return self.lambda(token);
+        }
+
+        let (token, matched) = self.does_any_token_type_match(&[TokenType::LeftBracket])?;
+        if matched {
+            return self.list(token);
+        }
+
+        // Statements starting with `{` are blocks, so a brace only gets here in expression position
+        let (token, matched) = self.does_any_token_type_match(&[TokenType::LeftBrace])?;
+        if matched {
+            return self.map(token);
+        }
+
+        let (token, matched) = self.does_any_token_type_match(&[TokenType::LeftParen])?;
         if matched {
+            if self.is_arrow_function() {
+                return self.arrow_function(token);
+            }
+
             let (_, expr) = self.expression()?;
             let last_visited_token = self.consume(
                 &TokenType::RightParen,
                 RoxyError::ParserError(ParserError::ExpectedRightParen(token.to_owned())),
             )?;
             return Ok((
-                last_visited_token,
+                last_visited_token.clone(),
                 Expr::Grouping(Grouping {
+                    id: next_expr_id(),
+                    span: token.span.merge(&last_visited_token.span),
                     expr: Box::new(expr),
                 }),
             ));
@@ -961,7 +1553,7 @@ impl Parser {
 
     fn match_token_types_and_create_literal(
         &mut self,
-        token_types: &Vec<TokenType>,
+        token_types: &[TokenType],
     ) -> Result<(Token, Option<Expr>), RoxyError> {
         let (token, matched) = self.does_any_token_type_match(token_types)?;
         if matched {
@@ -970,38 +1562,67 @@ impl Parser {
                     TokenType::String => Ok((
                         token,
                         Some(Expr::Literal(Literal {
+                            id: next_expr_id(),
+                            span: prev.span,
                             value: RoxyType::String(prev.literal.to_string()),
                         })),
                     )),
                     TokenType::Number => Ok((
                         token.clone(),
                         Some(Expr::Literal(Literal {
+                            id: next_expr_id(),
+                            span: prev.span,
                             value: RoxyType::Number(f64::try_conversion(prev.literal, token)?),
                         })),
                     )),
                     TokenType::False => Ok((
                         token,
                         Some(Expr::Literal(Literal {
+                            id: next_expr_id(),
+                            span: prev.span,
                             value: RoxyType::Boolean(false),
                         })),
                     )),
                     TokenType::True => Ok((
                         token,
                         Some(Expr::Literal(Literal {
+                            id: next_expr_id(),
+                            span: prev.span,
                             value: RoxyType::Boolean(true),
                         })),
                     )),
                     TokenType::Nil => Ok((
                         token,
                         Some(Expr::Literal(Literal {
+                            id: next_expr_id(),
+                            span: prev.span,
                             value: RoxyType::NULL,
                         })),
                     )),
-                    TokenType::This => Ok((token, Some(Expr::This(This { keyword: prev })))),
-                    TokenType::Super => Ok((token, Some(Expr::This(This { keyword: prev })))),
-                    TokenType::Identifier => {
-                        Ok((token, Some(Expr::Variable(Variable { name: prev }))))
-                    }
+                    TokenType::This => Ok((
+                        token,
+                        Some(Expr::This(This {
+                            id: next_expr_id(),
+                            span: prev.span,
+                            keyword: prev,
+                        })),
+                    )),
+                    TokenType::Super => Ok((
+                        token,
+                        Some(Expr::This(This {
+                            id: next_expr_id(),
+                            span: prev.span,
+                            keyword: prev,
+                        })),
+                    )),
+                    TokenType::Identifier => Ok((
+                        token,
+                        Some(Expr::Variable(Variable {
+                            id: next_expr_id(),
+                            span: prev.span,
+                            name: prev,
+                        })),
+                    )),
                     _ => return Ok((token.clone(), None)),
                 },
                 None => Err(RoxyError::ParserError(ParserError::InvalidTokenAccess(
@@ -1017,7 +1638,7 @@ impl Parser {
     // this is because we need this token in upper APIs to do appropriate error reporting
     fn does_any_token_type_match(
         &mut self,
-        token_types: &Vec<TokenType>,
+        token_types: &[TokenType],
     ) -> Result<(Token, bool), RoxyError> {
         let mut last_visited_token: Token;
         let (curr_token, _) = self.check(&token_types[0])?;
@@ -1030,7 +1651,7 @@ impl Parser {
             }
 
             let token_type = &token_types[i];
-            let (token, matched) = self.check(&token_type)?;
+            let (token, matched) = self.check(token_type)?;
             last_visited_token = token;
 
             if matched {
@@ -1047,7 +1668,7 @@ impl Parser {
     // Make this return the token
     fn check(&self, token_type: &TokenType) -> Result<(Token, bool), RoxyError> {
         if let Some(token) = self.peek() {
-            let matched = token.token_type == token_type.to_owned();
+            let matched = token.token_type == *token_type;
             return Ok((token, matched));
         }
 
@@ -1067,6 +1688,14 @@ impl Parser {
         }
     }
 
+    // Span of the last consumed token, used to close the span of the node being parsed
+    fn previous_span(&self) -> Span {
+        match self.previous() {
+            Some(token) => token.span,
+            None => Span::default(),
+        }
+    }
+
     fn previous(&self) -> Option<Token> {
         return Some(self.tokens.get(self.current - 1)?.to_owned());
     }
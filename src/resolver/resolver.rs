@@ -1,14 +1,12 @@
 use std::collections::HashMap;
 
 use crate::{
-    expr::{Expr, Function, Stmt},
-    interpreter::Interpreter,
+    expr::{Expr, ExprId, Function, Stmt},
+    interpreter::{module, Interpreter},
     utils::errors::{ResolutionError, RoxyError},
     Token,
 };
 
-//TODO: When break statement is implemented add a check for them to be present only in loops,
-//similar to the way we did here for return statement
 //TODO: Extend the resolver to report an error if a local variable is never used.
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -16,6 +14,7 @@ pub enum FunctionType {
     None,
     Function,
     Initializer,
+    Lambda,
     Method,
 }
 
@@ -26,12 +25,19 @@ pub enum ClassType {
     Subclass,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LoopType {
+    None,
+    Loop,
+}
+
 pub struct Resolver<'a> {
     interpreter: &'a mut Interpreter,
     //We have use this as a stack
     scopes: Vec<HashMap<String, bool>>,
     curr_func_type: FunctionType,
     curr_class_type: ClassType,
+    curr_loop_type: LoopType,
 }
 
 impl<'a> Resolver<'a> {
@@ -41,6 +47,7 @@ impl<'a> Resolver<'a> {
             scopes: vec![],
             curr_func_type: FunctionType::None,
             curr_class_type: ClassType::None,
+            curr_loop_type: LoopType::None,
         }
     }
 
@@ -58,12 +65,10 @@ impl<'a> Resolver<'a> {
                 )),
             }?;
 
-            if !is_define {
-                if scope.contains_key(&name.lexeme) {
-                    return Err(RoxyError::ResolutionError(
-                        ResolutionError::AlreadyAVariableWithThisNameInThisScope(name.clone()),
-                    ));
-                }
+            if !is_define && scope.contains_key(&name.lexeme) {
+                return Err(RoxyError::ResolutionError(
+                    ResolutionError::AlreadyAVariableWithThisNameInThisScope(name.clone()),
+                ));
             }
 
             scope.insert(name.lexeme, is_define);
@@ -84,10 +89,11 @@ impl<'a> Resolver<'a> {
         Ok(())
     }
 
-    fn resolve_expr(&mut self, expr: Expr) -> Result<(), RoxyError> {
+    pub fn resolve_expr(&mut self, expr: Expr) -> Result<(), RoxyError> {
         match expr {
             Expr::Assign(assign_expr) => {
                 self.resolve_expr(*assign_expr.value)?;
+                self.resolve_local(assign_expr.id, assign_expr.name)?;
             }
             Expr::Binary(binary_expr) => {
                 self.resolve_expr(*binary_expr.left)?;
@@ -105,11 +111,45 @@ impl<'a> Resolver<'a> {
             Expr::Grouping(grouping_expr) => {
                 self.resolve_expr(*grouping_expr.expr)?;
             }
+            Expr::Index(index_expr) => {
+                self.resolve_expr(*index_expr.object)?;
+                self.resolve_expr(*index_expr.index)?;
+            }
+            Expr::IndexSet(index_set) => {
+                self.resolve_expr(*index_set.value)?;
+                self.resolve_expr(*index_set.object)?;
+                self.resolve_expr(*index_set.index)?;
+            }
+            Expr::Lambda(lambda_expr) => {
+                let func = Function {
+                    span: lambda_expr.span,
+                    name: lambda_expr.keyword,
+                    params: lambda_expr.params,
+                    body: lambda_expr.body,
+                };
+                self.resolve_func(func, FunctionType::Lambda)?;
+            }
+            Expr::Interpolation(interpolation) => {
+                for part in interpolation.parts {
+                    self.resolve_expr(part)?;
+                }
+            }
+            Expr::List(list_expr) => {
+                for element in list_expr.elements {
+                    self.resolve_expr(element)?;
+                }
+            }
             Expr::Literal(_) => {}
             Expr::Logical(logical_expr) => {
                 self.resolve_expr(*logical_expr.left)?;
                 self.resolve_expr(*logical_expr.right)?;
             }
+            Expr::Map(map_expr) => {
+                for (key, value) in map_expr.entries {
+                    self.resolve_expr(key)?;
+                    self.resolve_expr(value)?;
+                }
+            }
             Expr::Set(set_expr) => {
                 self.resolve_expr(*set_expr.value)?;
                 self.resolve_expr(*set_expr.object)?;
@@ -126,7 +166,7 @@ impl<'a> Resolver<'a> {
                     ))
                 }
                 ClassType::Subclass => {
-                    self.resolve_local(Expr::Super(super_expr.clone()), super_expr.keyword)?;
+                    self.resolve_local(super_expr.id, super_expr.keyword)?;
                 }
             },
             Expr::This(ref this_expr) => {
@@ -136,7 +176,7 @@ impl<'a> Resolver<'a> {
                     ));
                 }
 
-                self.resolve_local(expr.clone(), this_expr.keyword.clone())?;
+                self.resolve_local(this_expr.id, this_expr.keyword.clone())?;
             }
             Expr::Unary(unary_expr) => {
                 self.resolve_expr(*unary_expr.right)?;
@@ -154,10 +194,10 @@ impl<'a> Resolver<'a> {
                                     ),
                                 ));
                             } else {
-                                self.resolve_local(expr.clone(), var_expr.name.clone())?;
+                                self.resolve_local(var_expr.id, var_expr.name.clone())?;
                             }
                         } else {
-                            self.resolve_local(expr.clone(), var_expr.name.clone())?;
+                            self.resolve_local(var_expr.id, var_expr.name.clone())?;
                         }
                     }
                 }
@@ -167,13 +207,13 @@ impl<'a> Resolver<'a> {
         Ok(())
     }
 
-    fn resolve_local(&mut self, expr: Expr, name: Token) -> Result<(), RoxyError> {
+    fn resolve_local(&mut self, id: ExprId, name: Token) -> Result<(), RoxyError> {
         if !self.scopes.is_empty() {
             let len = self.scopes.len();
             let mut i = len - 1;
             loop {
                 if self.scopes[i].contains_key(&name.lexeme) {
-                    self.interpreter.resolve(expr, len - 1 - i);
+                    self.interpreter.resolve(id, len - 1 - i);
                     return Ok(());
                 }
 
@@ -205,8 +245,6 @@ impl<'a> Resolver<'a> {
                         ));
                     }
 
-                    self.begin_scope();
-
                     self.curr_class_type = ClassType::Subclass;
                     self.resolve_expr(Expr::Variable(superclass.clone()))?;
                     self.begin_scope();
@@ -219,13 +257,11 @@ impl<'a> Resolver<'a> {
                     }
                 }
 
-                if !self.scopes.is_empty() {
-                    let len = self.scopes.len();
-                    let scope_opt = self.scopes.get_mut(len - 1);
-                    if let Some(scope) = scope_opt {
-                        scope.insert("this".into(), true);
-                    }
+                self.begin_scope();
+                if let Some(scope) = self.scopes.last_mut() {
+                    scope.insert("this".into(), true);
                 }
+
                 for method in class_stmt.methods {
                     let mut declaration = FunctionType::Method;
                     if method.name.lexeme.eq("init") {
@@ -267,7 +303,73 @@ impl<'a> Resolver<'a> {
             }
             Stmt::While(while_stmt) => {
                 self.resolve_expr(while_stmt.condition)?;
+                if let Some(increment) = while_stmt.increment {
+                    self.resolve_expr(*increment)?;
+                }
+
+                let enclosing_loop_type = self.curr_loop_type;
+                self.curr_loop_type = LoopType::Loop;
                 self.resolve_stmt(*while_stmt.body)?;
+                self.curr_loop_type = enclosing_loop_type;
+            }
+            Stmt::Break(break_stmt) => {
+                if self.curr_loop_type == LoopType::None {
+                    return Err(RoxyError::ResolutionError(
+                        ResolutionError::CantBreakOutsideOfALoop(break_stmt.keyword),
+                    ));
+                }
+            }
+            Stmt::Continue(continue_stmt) => {
+                if self.curr_loop_type == LoopType::None {
+                    return Err(RoxyError::ResolutionError(
+                        ResolutionError::CantContinueOutsideOfALoop(continue_stmt.keyword),
+                    ));
+                }
+            }
+            Stmt::Import(import) => {
+                // The path is relative to the file, so an import has to run when the file does
+                if !self.scopes.is_empty() {
+                    return Err(RoxyError::ResolutionError(
+                        ResolutionError::ImportOutsideOfTopLevel(import.keyword),
+                    ));
+                }
+
+                // A module which can't be read or parsed is reported when the import runs
+                let parsed = self.interpreter.module_path(&import.path).and_then(|path| {
+                    let stmts = self.interpreter.parse_module(&path, &import.path)?;
+                    Ok((path, stmts))
+                });
+                if let Ok((path, stmts)) = parsed {
+                    let exported = module::exported_names(&stmts);
+                    for name in import.names {
+                        if !exported.contains(&name.lexeme) {
+                            return Err(RoxyError::ResolutionError(
+                                ResolutionError::UndefinedImport(module::display_name(&path), name),
+                            ));
+                        }
+                    }
+                }
+            }
+            Stmt::Throw(throw) => self.resolve_expr(throw.value)?,
+            Stmt::Try(try_stmt) => {
+                self.begin_scope();
+                self.resolve(try_stmt.body)?;
+                self.end_scope();
+
+                // The caught error lives in the same scope as the catch body
+                if let Some(catch) = try_stmt.catch {
+                    self.begin_scope();
+                    self.declare_or_define(catch.name.clone(), false)?;
+                    self.declare_or_define(catch.name, true)?;
+                    self.resolve(catch.body)?;
+                    self.end_scope();
+                }
+
+                if let Some(finally) = try_stmt.finally {
+                    self.begin_scope();
+                    self.resolve(finally)?;
+                    self.end_scope();
+                }
             }
             Stmt::Return(return_stmt) => {
                 if self.curr_func_type == FunctionType::None {
@@ -298,6 +400,9 @@ impl<'a> Resolver<'a> {
     ) -> Result<(), RoxyError> {
         let enclosing_func_type = self.curr_func_type;
         self.curr_func_type = func_type;
+        // A loop around the declaration can't be broken out of from inside the function
+        let enclosing_loop_type = self.curr_loop_type;
+        self.curr_loop_type = LoopType::None;
 
         self.begin_scope();
         for param in func_stmt.params {
@@ -308,6 +413,7 @@ impl<'a> Resolver<'a> {
         self.end_scope();
 
         self.curr_func_type = enclosing_func_type;
+        self.curr_loop_type = enclosing_loop_type;
 
         Ok(())
     }
@@ -1,58 +1,197 @@
 use std::collections::HashMap;
 
+use crate::span::Span;
+use crate::tokens::{Trivia, TriviaKind};
 use crate::utils::errors::{CompileTimeError, RoxyError};
 use crate::Token;
 use crate::{tokens::TokenType, RoxyType};
 
+// A `${` inside a string whose closing `}` hasn't been reached yet. Braces opened inside the
+// interpolated expression are counted, so that the `}` ending it can be told apart from theirs
+struct OpenInterpolation {
+    triple: bool,
+    braces: usize,
+    // Position of the `${`
+    start: (usize, usize, usize),
+}
+
+// Walks the source once, front to back. Positions are byte offsets into `source`, so looking at
+// the next char and slicing out a lexeme don't depend on how far into the source the scanner is.
+//
+// Tokens are scanned as they are asked for, a `Scanner` is an iterator over them ending with the
+// `EOF` token, or with the first error. `scan_tokens` collects all of them for the parser.
+// A scanner made `with_trivia` also hands out the whitespace and comments in between, attached to
+// the tokens: everything up to the end of a token's line is its trailing trivia, the rest is the
+// leading trivia of the next token
 pub struct Scanner {
     source: String,
     tokens: Vec<Token>,
-    start: usize,
-    current: usize,
     line: usize,
+    // Byte offset and column of the next char, kept in sync by `advance`
+    offset: usize,
+    column: usize,
+    // Position where the token being scanned began
+    start_offset: usize,
+    start_line: usize,
+    start_column: usize,
     keywords: HashMap<String, TokenType>,
+    interpolations: Vec<OpenInterpolation>,
+    // Token scanned by `scan_token` which hasn't been handed out yet
+    pending: Option<Token>,
+    // Type of the last token handed out
+    previous: Option<TokenType>,
+    keep_trivia: bool,
+    // Trivia seen since the last token
+    trivia: Vec<Trivia>,
+    finished: bool,
 }
 
-//TODO: Fix multiline comments
 impl Scanner {
     pub fn new(source: String) -> Self {
         Self {
             source,
             tokens: vec![],
-            start: 0,
-            current: 0,
-            line: 0,
+            line: 1,
+            offset: 0,
+            column: 1,
+            start_offset: 0,
+            start_line: 1,
+            start_column: 1,
             keywords: construct_keywords(),
+            interpolations: vec![],
+            pending: None,
+            previous: None,
+            keep_trivia: false,
+            trivia: vec![],
+            finished: false,
         }
     }
 
+    // Keeps whitespace and comments as trivia on the tokens, for tools which need to give back
+    // the source as it was written
+    pub fn with_trivia(mut self, keep_trivia: bool) -> Self {
+        self.keep_trivia = keep_trivia;
+        self
+    }
+
     pub fn scan_tokens(&mut self) -> Result<&Vec<Token>, RoxyError> {
-        while !self.is_at_end() {
-            self.start = self.current;
+        while let Some(token) = self.next() {
+            self.tokens.push(token?);
+        }
+
+        return Ok(&self.tokens);
+    }
+
+    fn next_token(&mut self) -> Result<Token, RoxyError> {
+        let mut token = loop {
+            if let Some(token) = self.pending.take() {
+                break token;
+            }
+
+            if self.is_at_end() {
+                return self.eof();
+            }
+
+            self.start_offset = self.offset;
+            self.start_line = self.line;
+            self.start_column = self.column;
             self.scan_token()?;
+        };
+
+        token.leading_trivia = std::mem::take(&mut self.trivia);
+        if self.keep_trivia {
+            token.trailing_trivia = self.trailing_trivia();
         }
+        self.previous = Some(token.token_type.clone());
 
-        self.tokens.push(Token {
+        return Ok(token);
+    }
+
+    fn eof(&mut self) -> Result<Token, RoxyError> {
+        if let Some(open) = self.interpolations.last() {
+            return Err(self.syntax_error(
+                self.span_from(open.start),
+                "unterminated interpolation, expected `}`",
+            ));
+        }
+
+        return Ok(Token {
             token_type: TokenType::EOF,
             lexeme: String::new(),
             literal: RoxyType::NULL,
             line: self.line,
+            span: Span::new(
+                self.offset,
+                self.offset,
+                self.line,
+                self.column,
+                self.line,
+                self.column,
+            ),
+            leading_trivia: std::mem::take(&mut self.trivia),
+            trailing_trivia: vec![],
         });
+    }
 
-        return Ok(&self.tokens);
+    // Trivia after a token up to and including the end of its line. A comment which turns out
+    // to be unterminated is left to be reported when scanning carries on
+    fn trailing_trivia(&mut self) -> Vec<Trivia> {
+        loop {
+            let is_trivia = matches!(
+                (self.peek(), self.peek_next()),
+                (Some(' ' | '\t' | '\r' | '\n'), _) | (Some('/'), Some('/' | '*'))
+            );
+            if !is_trivia {
+                break;
+            }
+
+            let position = (self.offset, self.line, self.column);
+            let seen = self.trivia.len();
+            self.start_offset = self.offset;
+            self.start_line = self.line;
+            self.start_column = self.column;
+            if self.scan_token().is_err() {
+                (self.offset, self.line, self.column) = position;
+                self.trivia.truncate(seen);
+                break;
+            }
+
+            if self.line > position.1 {
+                break;
+            }
+        }
+
+        return std::mem::take(&mut self.trivia);
     }
 
     pub fn is_at_end(&self) -> bool {
-        return self.current > self.source.len() - 1;
+        return self.offset >= self.source.len();
     }
 
-    pub fn scan_token(&mut self) -> anyhow::Result<(), RoxyError> {
+    // Scans one lexeme. A token it makes is left in `pending`, trivia in `trivia`
+    fn scan_token(&mut self) -> anyhow::Result<(), RoxyError> {
         if let Some(c) = self.advance() {
             match c {
                 '(' => self.add_token(TokenType::LeftParen, None),
                 ')' => self.add_token(TokenType::RightParen, None),
-                '{' => self.add_token(TokenType::LeftBrace, None),
-                '}' => self.add_token(TokenType::RightBrace, None),
+                '{' => {
+                    if let Some(open) = self.interpolations.last_mut() {
+                        open.braces += 1;
+                    }
+                    self.add_token(TokenType::LeftBrace, None)
+                }
+                '}' => match self.interpolations.pop() {
+                    Some(open) if open.braces == 0 => self.end_interpolation(open)?,
+                    Some(mut open) => {
+                        open.braces -= 1;
+                        self.interpolations.push(open);
+                        self.add_token(TokenType::RightBrace, None)
+                    }
+                    None => self.add_token(TokenType::RightBrace, None),
+                },
+                '[' => self.add_token(TokenType::LeftBracket, None),
+                ']' => self.add_token(TokenType::RightBracket, None),
+                ':' => self.add_token(TokenType::Colon, None),
                 ',' => self.add_token(TokenType::Comma, None),
                 '.' => self.add_token(TokenType::Dot, None),
                 '-' => self.add_token(TokenType::Minus, None),
@@ -62,6 +201,8 @@ impl Scanner {
                 '=' => {
                     if self.lookahead_one_step('=') {
                         self.add_token(TokenType::EqualEqual, None);
+                    } else if self.lookahead_one_step('>') {
+                        self.add_token(TokenType::Arrow, None);
                     } else {
                         self.add_token(TokenType::Equal, None)
                     }
@@ -95,72 +236,31 @@ impl Scanner {
                             }
                             self.advance();
                         }
+                        self.add_trivia(TriviaKind::LineComment);
                     } else if self.lookahead_one_step('*') {
-                        let mut slash_star_encountered = 1;
-                        while let Some(ch) = self.peek() {
-                            if ch == '\n' {
-                                self.line += 1;
-                            }
-
-                            if ch == '/' {
-                                if let Some(ch_next) = self.peek_next() {
-                                    if ch_next == '*' {
-                                        self.advance();
-                                        self.advance();
-                                        slash_star_encountered += 1;
-                                    }
-                                    continue;
-                                } else {
-                                    return Err(RoxyError::SyntaxError(CompileTimeError {
-                                        line: self.line,
-                                        where_in_file: String::new(),
-                                        message: String::from("Unterminated multiline comment"),
-                                    }));
-                                }
-                            }
-
-                            if ch == '*' {
-                                if let Some(ch_next) = self.peek_next() {
-                                    if ch_next == '/' {
-                                        self.advance();
-                                        self.advance();
-                                        slash_star_encountered -= 1;
-                                        if slash_star_encountered == 0 {
-                                            break;
-                                        }
-                                    }
-                                    continue;
-                                } else {
-                                    return Err(RoxyError::SyntaxError(CompileTimeError {
-                                        line: self.line,
-                                        where_in_file: String::new(),
-                                        message: String::from("Unterminated multiline comment"),
-                                    }));
-                                }
-                            }
-                            self.advance();
-                        }
+                        self.block_comment()?;
+                        self.add_trivia(TriviaKind::BlockComment);
                     } else {
                         self.add_token(TokenType::Slash, None)
                     }
                 }
-                ' ' => {}
-                '\r' => {}
-                '\t' => {}
-                '\n' => {
-                    self.line += 1;
-                }
-                '"' => self.string()?,
+                ' ' | '\r' | '\t' => self.add_trivia(TriviaKind::Whitespace),
+                '\n' => self.add_trivia(TriviaKind::Newline),
+                '"' => self.string(false)?,
                 _ => {
-                    if self.is_digit(c) {
-                        self.number();
+                    if c == 'r' && self.peek() == Some('"') {
+                        self.advance();
+                        self.string(true)?;
+                    } else if self.is_digit(c) {
+                        self.number()?;
                     } else if self.is_alpha(c) {
                         self.identifier();
                     } else {
                         return Err(RoxyError::SyntaxError(CompileTimeError {
                             line: self.line,
                             where_in_file: String::new(),
-                            message: String::from("Unparsable token"),
+                            message: String::from("unparsable token"),
+                            span: self.current_span(),
                         }));
                     }
                 }
@@ -172,9 +272,43 @@ impl Scanner {
             line: self.line,
             where_in_file: String::new(),
             message: String::from("panic in scanner advancing"),
+            span: self.current_span(),
         }))
     }
 
+    // Called with the opening `/*` consumed. Block comments nest
+    fn block_comment(&mut self) -> Result<(), RoxyError> {
+        let mut slash_star_encountered = 1;
+        loop {
+            match (self.peek(), self.peek_next()) {
+                (Some('/'), Some('*')) => {
+                    self.advance();
+                    self.advance();
+                    slash_star_encountered += 1;
+                }
+                (Some('*'), Some('/')) => {
+                    self.advance();
+                    self.advance();
+                    slash_star_encountered -= 1;
+                    if slash_star_encountered == 0 {
+                        return Ok(());
+                    }
+                }
+                (Some(_), _) => {
+                    self.advance();
+                }
+                (None, _) => {
+                    return Err(RoxyError::SyntaxError(CompileTimeError {
+                        line: self.line,
+                        where_in_file: String::new(),
+                        message: String::from("unterminated multiline comment"),
+                        span: self.current_span(),
+                    }));
+                }
+            }
+        }
+    }
+
     fn identifier(&mut self) {
         while let Some(ch) = self.peek() {
             if !self.is_alpha_numeric(ch) {
@@ -183,8 +317,7 @@ impl Scanner {
             self.advance();
         }
 
-        let chars: Vec<char> = self.source.chars().collect();
-        let value: String = chars[self.start..self.current].iter().collect();
+        let value = self.lexeme().to_string();
 
         let token_type: TokenType;
         if let Some(kv) = self.keywords.get_key_value(&value) {
@@ -200,132 +333,399 @@ impl Scanner {
         return self.is_alpha(ch) || self.is_digit(ch);
     }
 
-    fn number(&mut self) {
+    // Takes in everything that could be part of the literal, so that `0b12` or `1e` is reported as
+    // a malformed number rather than scanned as a number followed by something else
+    fn number(&mut self) -> Result<(), RoxyError> {
+        let decimal = !matches!(
+            (self.lexeme(), self.peek()),
+            ("0", Some('x')) | ("0", Some('b')) | ("0", Some('o'))
+        );
+        let mut previous = self.lexeme().chars().next();
+        let mut seen_dot = false;
+
         while let Some(ch) = self.peek() {
-            if !self.is_digit(ch) {
+            let fraction = ch == '.'
+                && decimal
+                && !seen_dot
+                && self.peek_next().is_some_and(|next| self.is_digit(next));
+            let exponent_sign =
+                (ch == '+' || ch == '-') && decimal && matches!(previous, Some('e' | 'E'));
+            if !(self.is_alpha_numeric(ch) || fraction || exponent_sign) {
                 break;
             }
-            self.advance();
+
+            seen_dot |= fraction;
+            previous = self.advance();
+        }
+
+        match parse_number(self.lexeme()) {
+            Ok(num) => self.add_token(TokenType::Number, Some(RoxyType::Number(num))),
+            Err(message) => return Err(self.syntax_error(self.current_span(), &message)),
         }
 
-        if let Some(ch) = self.peek_next() {
-            if self.peek() == Some('.') && self.is_digit(ch) {
+        Ok(())
+    }
+
+    fn is_digit(&self, ch: char) -> bool {
+        return ch.is_ascii_digit();
+    }
+
+    fn is_alpha(&self, ch: char) -> bool {
+        return ch.is_ascii_alphabetic() || ch == '_';
+    }
+
+    // Called with the opening `"` of the string, or `r"` for a raw one, already consumed. Strings
+    // in triple quotes can hold a lone `"` and start on the line after the quotes when those are
+    // the last thing on their line. Raw strings keep backslashes as they are
+    fn string(&mut self, raw: bool) -> Result<(), RoxyError> {
+        let triple = self.peek() == Some('"') && self.peek_next() == Some('"');
+        if triple {
+            self.advance();
+            self.advance();
+            if self.peek() == Some('\n') {
                 self.advance();
             }
         }
 
-        while let Some(ch) = self.peek() {
-            if !self.is_digit(ch) {
+        return self.string_segment(raw, triple);
+    }
+
+    // Scans up to the end of the string or the next `${`. A string with interpolations becomes an
+    // `Interpolation` token for every piece ending in `${`, the tokens of each expression, and a
+    // `String` token for the piece after the last `}`:
+    //
+    // "a ${b} c"  ->  Interpolation `"a ${`, Identifier `b`, String `} c"`
+    fn string_segment(&mut self, raw: bool, triple: bool) -> Result<(), RoxyError> {
+        let mut value = String::new();
+        loop {
+            let ch = match self.peek() {
+                Some(ch) => ch,
+                None => return Err(self.syntax_error(self.current_span(), "unterminated string")),
+            };
+
+            if ch == '"' && (!triple || self.source[self.offset..].starts_with("\"\"\"")) {
+                let quotes = if triple { 3 } else { 1 };
+                for _ in 0..quotes {
+                    self.advance();
+                }
                 break;
             }
+
+            if ch == '$' && self.peek_next() == Some('{') && !raw {
+                let start = (self.offset, self.line, self.column);
+                self.advance();
+                self.advance();
+                self.add_token(TokenType::Interpolation, Some(RoxyType::String(value)));
+                self.interpolations.push(OpenInterpolation {
+                    triple,
+                    braces: 0,
+                    start,
+                });
+                return Ok(());
+            }
+
+            let escape_start = (self.offset, self.line, self.column);
             self.advance();
+            if ch == '\\' && !raw {
+                value.push(self.escape(escape_start)?);
+            } else {
+                value.push(ch);
+            }
         }
 
-        let chars: Vec<char> = self.source.chars().collect();
-        let value: String = chars[self.start..self.current].iter().collect();
-        if let Ok(num) = value.parse::<f64>() {
-            self.add_token(TokenType::Number, Some(RoxyType::Number(num)));
-        }
-    }
+        self.add_token(TokenType::String, Some(RoxyType::String(value)));
 
-    fn is_digit(&self, ch: char) -> bool {
-        return ch >= '0' && ch <= '9';
+        Ok(())
     }
 
-    fn is_alpha(&self, ch: char) -> bool {
-        return (ch >= 'a' && ch <= 'z') || (ch >= 'A' && ch <= 'Z') || ch == '_';
+    // Called with the `}` closing an interpolated expression consumed, the rest of the string
+    // follows
+    fn end_interpolation(&mut self, open: OpenInterpolation) -> Result<(), RoxyError> {
+        if self.previous == Some(TokenType::Interpolation) {
+            return Err(self.syntax_error(
+                self.span_from(open.start),
+                "expected expression inside `${}`",
+            ));
+        }
+
+        return self.string_segment(false, open.triple);
     }
 
-    fn string(&mut self) -> Result<(), RoxyError> {
-        while self.peek() != Some('"') && self.peek() != None {
-            if self.peek() == Some('\n') {
-                self.line += 1;
+    // The char an escape sequence stands for, `start` is the position of its backslash
+    fn escape(&mut self, start: (usize, usize, usize)) -> Result<char, RoxyError> {
+        let ch = match self.advance() {
+            Some(ch) => ch,
+            None => return Err(self.syntax_error(self.current_span(), "unterminated string")),
+        };
+
+        match ch {
+            'n' => return Ok('\n'),
+            't' => return Ok('\t'),
+            'r' => return Ok('\r'),
+            '0' => return Ok('\0'),
+            '"' => return Ok('"'),
+            '\\' => return Ok('\\'),
+            '$' => return Ok('$'),
+            'u' => {}
+            _ => {
+                let message = format!("invalid escape sequence `\\{}`", ch.escape_default());
+                return Err(self.syntax_error(self.span_from(start), &message));
             }
-            self.advance();
         }
 
-        if self.is_at_end() {
-            return Err(RoxyError::SyntaxError(CompileTimeError {
-                line: self.line,
-                where_in_file: String::new(),
-                message: String::from("Unterminated String"),
-            }));
+        // `\u{...}` with 1 to 6 hex digits naming a Unicode scalar value
+        if !self.lookahead_one_step('{') {
+            return Err(self.syntax_error(
+                self.span_from(start),
+                "expected `{` after `\\u` in unicode escape",
+            ));
         }
 
-        self.advance();
+        let mut digits = String::new();
+        while let Some(ch) = self.peek() {
+            if !ch.is_ascii_hexdigit() {
+                break;
+            }
+            digits.push(ch);
+            self.advance();
+        }
 
-        let chars: Vec<char> = self.source.chars().collect();
-        let value: String = chars[self.start + 1..self.current - 1].iter().collect();
-        self.add_token(TokenType::String, Some(RoxyType::String(value)));
+        if !self.lookahead_one_step('}') || digits.is_empty() || digits.len() > 6 {
+            return Err(self.syntax_error(
+                self.span_from(start),
+                "unicode escapes take 1 to 6 hex digits like `\\u{e9}`",
+            ));
+        }
 
-        Ok(())
+        match u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+        {
+            Some(ch) => return Ok(ch),
+            None => {
+                let message = format!("invalid unicode code point `{}` in escape", digits);
+                return Err(self.syntax_error(self.span_from(start), &message));
+            }
+        }
     }
 
     fn peek(&self) -> Option<char> {
-        self.source.chars().nth(self.current)
-        // if let Some(ch) =  {
-        //     return ch;
-        // } else {
-        //     return '\0';
-        // }
+        return self.source[self.offset..].chars().next();
     }
 
     fn peek_next(&self) -> Option<char> {
-        self.source.chars().nth(self.current + 1)
+        let mut chars = self.source[self.offset..].chars();
+        chars.next();
+        return chars.next();
     }
 
     fn lookahead_one_step(&mut self, ch: char) -> bool {
-        if let Some(curr_ch) = self.source.chars().nth(self.current) {
-            if ch != curr_ch {
-                return false;
-            }
-            self.current += 1;
-            return true;
+        if self.peek() != Some(ch) {
+            return false;
         }
 
-        return false;
+        self.advance();
+        return true;
     }
 
     fn advance(&mut self) -> Option<char> {
-        let ch = self.source.chars().nth(self.current);
-        self.current += 1;
-        return ch;
+        let ch = self.peek()?;
+        self.offset += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        return Some(ch);
+    }
+
+    // Source of the token being scanned
+    fn lexeme(&self) -> &str {
+        return &self.source[self.start_offset..self.offset];
+    }
+
+    // Span from an earlier `(offset, line, column)` upto the current position
+    fn span_from(&self, (offset, line, column): (usize, usize, usize)) -> Span {
+        Span::new(offset, self.offset, line, column, self.line, self.column)
+    }
+
+    fn syntax_error(&self, span: Span, message: &str) -> RoxyError {
+        return RoxyError::SyntaxError(CompileTimeError {
+            line: span.line,
+            where_in_file: String::new(),
+            message: message.to_string(),
+            span,
+        });
+    }
+
+    // Span from the start of the token being scanned upto the current position
+    fn current_span(&self) -> Span {
+        Span::new(
+            self.start_offset,
+            self.offset,
+            self.start_line,
+            self.start_column,
+            self.line,
+            self.column,
+        )
     }
 
     fn add_token(&mut self, token_type: TokenType, literal: Option<RoxyType>) {
-        let text = self.source[self.start..self.current].to_string();
+        let text = self.lexeme().to_string();
         let literal = match literal {
             Some(l) => l,
             None => RoxyType::NULL,
         };
-        self.tokens.push(Token {
+        self.pending = Some(Token {
             token_type,
             lexeme: text,
             literal,
-            line: self.line,
+            line: self.start_line,
+            span: self.current_span(),
+            leading_trivia: vec![],
+            trailing_trivia: vec![],
         });
     }
+
+    // Runs of whitespace are kept as one piece of trivia
+    fn add_trivia(&mut self, kind: TriviaKind) {
+        if !self.keep_trivia {
+            return;
+        }
+
+        let span = self.current_span();
+        if let Some(last) = self.trivia.last_mut() {
+            if kind == TriviaKind::Whitespace
+                && last.kind == TriviaKind::Whitespace
+                && last.span.end == self.start_offset
+            {
+                last.text
+                    .push_str(&self.source[self.start_offset..self.offset]);
+                last.span = last.span.merge(&span);
+                return;
+            }
+        }
+
+        self.trivia.push(Trivia {
+            kind,
+            text: self.lexeme().to_string(),
+            span,
+        });
+    }
+}
+
+impl Iterator for Scanner {
+    type Item = Result<Token, RoxyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let token = self.next_token();
+        self.finished = match &token {
+            Ok(token) => token.token_type == TokenType::EOF,
+            Err(_) => true,
+        };
+
+        return Some(token);
+    }
 }
 
 fn construct_keywords() -> HashMap<String, TokenType> {
     let mut keywords: HashMap<String, TokenType> = HashMap::new();
 
     keywords.insert(String::from("and"), TokenType::And);
+    keywords.insert(String::from("break"), TokenType::Break);
+    keywords.insert(String::from("catch"), TokenType::Catch);
     keywords.insert(String::from("class"), TokenType::Class);
+    keywords.insert(String::from("continue"), TokenType::Continue);
     keywords.insert(String::from("else"), TokenType::Else);
     keywords.insert(String::from("false"), TokenType::False);
+    keywords.insert(String::from("finally"), TokenType::Finally);
     keywords.insert(String::from("for"), TokenType::For);
     keywords.insert(String::from("fun"), TokenType::Fun);
     keywords.insert(String::from("if"), TokenType::If);
+    keywords.insert(String::from("import"), TokenType::Import);
     keywords.insert(String::from("nil"), TokenType::Nil);
     keywords.insert(String::from("or"), TokenType::Or);
     keywords.insert(String::from("print"), TokenType::Print);
     keywords.insert(String::from("return"), TokenType::Return);
     keywords.insert(String::from("super"), TokenType::Super);
     keywords.insert(String::from("this"), TokenType::This);
+    keywords.insert(String::from("throw"), TokenType::Throw);
     keywords.insert(String::from("true"), TokenType::True);
+    keywords.insert(String::from("try"), TokenType::Try);
     keywords.insert(String::from("var"), TokenType::Var);
     keywords.insert(String::from("while"), TokenType::While);
 
     return keywords;
 }
+
+// Value of a number literal like `42`, `1_000.5`, `6.02e23`, `0xFF`, `0b1010` or `0o755`.
+// Underscores can separate digits, but only ever sit between two of them
+fn parse_number(lexeme: &str) -> Result<f64, String> {
+    let (radix, name, digits) = match lexeme.get(..2) {
+        Some("0x") => (16, "hexadecimal", &lexeme[2..]),
+        Some("0b") => (2, "binary", &lexeme[2..]),
+        Some("0o") => (8, "octal", &lexeme[2..]),
+        _ => (10, "number", lexeme),
+    };
+
+    let chars: Vec<char> = digits.chars().collect();
+    for (i, ch) in chars.iter().enumerate() {
+        if *ch != '_' {
+            continue;
+        }
+        let between_digits = i > 0
+            && chars[i - 1].is_digit(radix)
+            && chars.get(i + 1).is_some_and(|next| next.is_digit(radix));
+        if !between_digits {
+            return Err(format!("`_` in `{}` has to be between two digits", lexeme));
+        }
+    }
+    let digits = digits.replace('_', "");
+
+    if radix != 10 {
+        if digits.is_empty() {
+            return Err(format!("expected digits after `{}`", &lexeme[..2]));
+        }
+
+        let mut value = 0.0;
+        for ch in digits.chars() {
+            match ch.to_digit(radix) {
+                Some(digit) => value = value * radix as f64 + digit as f64,
+                None => {
+                    return Err(format!(
+                        "invalid digit `{}` in {} literal `{}`",
+                        ch, name, lexeme
+                    ))
+                }
+            }
+        }
+        return Ok(value);
+    }
+
+    // Integer part, then an optional fraction and exponent
+    let mut rest = digits.trim_start_matches(|ch: char| ch.is_ascii_digit());
+    if let Some(fraction) = rest.strip_prefix('.') {
+        rest = fraction.trim_start_matches(|ch: char| ch.is_ascii_digit());
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        rest = exponent.trim_start_matches(|ch: char| ch.is_ascii_digit());
+        if rest.len() == exponent.len() {
+            return Err(format!("expected digits in the exponent of `{}`", lexeme));
+        }
+    }
+
+    if let Some(ch) = rest.chars().next() {
+        return Err(format!("invalid digit `{}` in number `{}`", ch, lexeme));
+    }
+
+    return digits
+        .parse::<f64>()
+        .map_err(|_| format!("malformed number `{}`", lexeme));
+}
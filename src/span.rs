@@ -0,0 +1,70 @@
+use std::fmt::Formatter;
+
+// A region of the source code. Offsets are byte offsets into the source, lines and columns are
+// 1 based and counted in chars. Both `end` and `end_column` point one past the last char
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    pub fn new(
+        start: usize,
+        end: usize,
+        line: usize,
+        column: usize,
+        end_line: usize,
+        end_column: usize,
+    ) -> Self {
+        Self {
+            start,
+            end,
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    // Smallest span covering both of the spans
+    pub fn merge(&self, other: &Span) -> Span {
+        let first = if self.start <= other.start {
+            self
+        } else {
+            other
+        };
+        let last = if self.end >= other.end { self } else { other };
+
+        Span {
+            start: first.start,
+            end: last.end,
+            line: first.line,
+            column: first.column,
+            end_line: last.end_line,
+            end_column: last.end_column,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}:{}-{}:{}",
+            self.line, self.column, self.end_line, self.end_column
+        )
+    }
+}
@@ -1,3 +1,5 @@
+use crate::span::Span;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TokenType {
     // Single-character tokens.
@@ -5,6 +7,9 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     Minus,
@@ -18,6 +23,7 @@ pub enum TokenType {
     BangEqual,
     Equal,
     EqualEqual,
+    Arrow,
     Greater,
     GreaterEqual,
     Less,
@@ -26,36 +32,48 @@ pub enum TokenType {
     // Literals.
     Identifier,
     String,
+    // Piece of a string up to a `${`
+    Interpolation,
     Number,
 
     // Keywords.
     And,
+    Break,
+    Catch,
     Class,
+    Continue,
     Else,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Import,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
     EOF,
 }
 
-impl ToString for TokenType {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for TokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let token_str = match self {
             TokenType::LeftParen => "(",
             TokenType::RightParen => ")",
             TokenType::LeftBrace => "{",
             TokenType::RightBrace => "}",
+            TokenType::LeftBracket => "[",
+            TokenType::RightBracket => "]",
+            TokenType::Colon => ":",
             TokenType::Comma => ",",
             TokenType::Dot => ".",
             TokenType::Minus => "-",
@@ -67,31 +85,58 @@ impl ToString for TokenType {
             TokenType::BangEqual => "!=",
             TokenType::Equal => "=",
             TokenType::EqualEqual => "==",
+            TokenType::Arrow => "=>",
             TokenType::Greater => ">",
             TokenType::GreaterEqual => ">=",
             TokenType::Less => "<",
             TokenType::LessEqual => "<=",
             TokenType::Identifier => "ident",
             TokenType::String => "String",
+            TokenType::Interpolation => "interpolation",
             TokenType::Number => "number",
             TokenType::And => "&&",
+            TokenType::Break => "break",
+            TokenType::Catch => "catch",
             TokenType::Class => "class",
+            TokenType::Continue => "continue",
             TokenType::Else => "else",
             TokenType::False => "false",
+            TokenType::Finally => "finally",
             TokenType::Fun => "fun",
             TokenType::For => "for",
-            TokenType::If => "for",
+            TokenType::If => "if",
+            TokenType::Import => "import",
             TokenType::Nil => "nil",
             TokenType::Or => "or",
             TokenType::Print => "print",
             TokenType::Return => "return",
             TokenType::Super => "super",
             TokenType::This => "this",
+            TokenType::Throw => "throw",
             TokenType::True => "true",
+            TokenType::Try => "try",
             TokenType::Var => "var",
             TokenType::While => "while",
             TokenType::EOF => "eof",
         };
-        String::from(token_str)
+        write!(f, "{}", token_str)
     }
 }
+
+// Source between tokens, which the parser has no use for. Only kept by a scanner made
+// `with_trivia`, see `Scanner`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    // Spaces, tabs and carriage returns
+    Whitespace,
+    Newline,
+    LineComment,
+    BlockComment,
+}
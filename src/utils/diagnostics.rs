@@ -0,0 +1,229 @@
+use std::path::Path;
+
+use crate::utils::errors::{RoxyError, StackFrame};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+// Renders errors in the style of rustc:
+//
+// error[E0306]: division by zero
+//  --> examples/div.rx:1:9
+//   |
+// 1 | print 1 / 0;
+//   |         ^
+//   = help: check the divisor before dividing
+pub struct DiagnosticRenderer<'a> {
+    file_name: &'a str,
+    source: &'a str,
+    color: bool,
+}
+
+impl<'a> DiagnosticRenderer<'a> {
+    pub fn new(file_name: &'a str, source: &'a str) -> Self {
+        Self {
+            file_name,
+            source,
+            color: true,
+        }
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn render(&self, err: &RoxyError) -> String {
+        let mut out = format!(
+            "{}: {}\n",
+            self.paint(RED, &format!("error[{}]", err.code())),
+            self.paint(BOLD, &err.message())
+        );
+
+        let span = match err.span() {
+            // Tokens made up by an embedding host point at line 0, there is no source to show
+            Some(span) if span.line > 0 => span,
+            _ => {
+                out.push_str(&format!("{} {}\n", self.paint(BLUE, "-->"), self.file_name));
+                return self.render_help(err, out, 0);
+            }
+        };
+
+        let line_no = span.line.to_string();
+        let gutter = " ".repeat(line_no.len());
+        out.push_str(&format!(
+            "{}{} {}:{}:{}\n",
+            gutter,
+            self.paint(BLUE, "-->"),
+            self.file_name,
+            span.line,
+            span.column
+        ));
+
+        let line = self.source.lines().nth(span.line - 1).unwrap_or("");
+        // Spans running over multiple lines are underlined till the end of their first line
+        let end_column = if span.end_line == span.line {
+            span.end_column
+        } else {
+            line.chars().count() + 1
+        };
+        let underline_len = end_column.saturating_sub(span.column).max(1);
+
+        out.push_str(&format!("{} {}\n", gutter, self.paint(BLUE, "|")));
+        out.push_str(&format!(
+            "{} {} {}\n",
+            self.paint(BLUE, &line_no),
+            self.paint(BLUE, "|"),
+            line
+        ));
+        out.push_str(&format!(
+            "{} {} {}{}\n",
+            gutter,
+            self.paint(BLUE, "|"),
+            " ".repeat(span.column.saturating_sub(1)),
+            self.paint(RED, &"^".repeat(underline_len))
+        ));
+
+        self.render_help(err, out, gutter.len())
+    }
+
+    // One JSON object on a single line, for editors and CI. Errors without a location carry
+    // `null` positions:
+    //
+    // {"severity":"error","kind":"InterpreterError","code":"E0306","message":"division by zero",
+    //  "help":"...","file":"div.rx","start":{"line":1,"column":9,"offset":8},"end":{...}}
+    pub fn render_json(&self, err: &RoxyError) -> String {
+        let help = match err.help() {
+            Some(help) => json_string(help),
+            None => "null".to_string(),
+        };
+        let (start, end) = match err.span() {
+            Some(span) => (
+                json_position(span.line, span.column, span.start),
+                json_position(span.end_line, span.end_column, span.end),
+            ),
+            None => ("null".to_string(), "null".to_string()),
+        };
+
+        format!(
+            "{{\"severity\":\"error\",\"kind\":{},\"code\":{},\"message\":{},\"help\":{},\"file\":{},\"start\":{},\"end\":{}}}\n",
+            json_string(err.kind()),
+            json_string(err.code()),
+            json_string(&err.message()),
+            help,
+            json_string(self.file_name),
+            start,
+            end
+        )
+    }
+
+    // Calls an uncaught error unwound through, printed after its diagnostic. Nothing for an error
+    // raised in top-level code. Functions from other modules are shown in their own file:
+    //
+    // stack trace:
+    //   in divide() at examples/lib/math.rx:2
+    //   in average() at examples/div.rx:6
+    //   in script at examples/div.rx:9
+    pub fn render_trace(&self, err: &RoxyError, trace: &[StackFrame]) -> String {
+        if trace.is_empty() {
+            return String::new();
+        }
+
+        let mut out = format!("{}\n", self.paint(BOLD, "stack trace:"));
+        // Each frame was called from the line of the next one
+        let mut line = err.span().map(|span| span.line).unwrap_or_default();
+        for frame in trace {
+            let file = match &frame.file {
+                Some(path) if !self.is_file(path) => display_path(path),
+                _ => self.file_name.to_string(),
+            };
+            out.push_str(&format!("  in {}() at {}:{}\n", frame.function, file, line));
+            line = frame.line;
+        }
+        out.push_str(&format!("  in script at {}:{}\n", self.file_name, line));
+
+        out
+    }
+
+    // Whether the path is the file being rendered, paths of running code are canonical
+    pub fn is_file(&self, path: &Path) -> bool {
+        return Path::new(self.file_name)
+            .canonicalize()
+            .is_ok_and(|file| file == path);
+    }
+
+    fn render_help(&self, err: &RoxyError, mut out: String, gutter_len: usize) -> String {
+        if let Some(help) = err.help() {
+            out.push_str(&format!(
+                "{} {} {}\n",
+                " ".repeat(gutter_len),
+                self.paint(BLUE, "="),
+                self.paint(BOLD, &format!("help: {}", help))
+            ));
+        }
+
+        out
+    }
+
+    fn paint(&self, color: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+// Relative to the working directory when the file is inside of it, like the paths people type
+pub fn display_path(path: &Path) -> String {
+    let relative = std::env::current_dir()
+        .ok()
+        .and_then(|dir| path.strip_prefix(dir).ok().map(Path::to_path_buf));
+    return relative
+        .unwrap_or_else(|| path.to_path_buf())
+        .display()
+        .to_string();
+}
+
+fn json_position(line: usize, column: usize, offset: usize) -> String {
+    format!(
+        "{{\"line\":{},\"column\":{},\"offset\":{}}}",
+        line, column, offset
+    )
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,4 +1,7 @@
-use crate::Token;
+use std::path::PathBuf;
+
+use crate::span::Span;
+use crate::{RoxyType, Token};
 
 #[derive(Debug, Clone)]
 pub enum RoxyError {
@@ -13,6 +16,77 @@ pub enum RoxyError {
 
 impl std::error::Error for RoxyError {}
 
+// Pieces a diagnostic is built out of, see `utils::diagnostics`
+impl RoxyError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RoxyError::SyntaxError(_) => "E0001",
+            RoxyError::ParserError(err) => err.code(),
+            RoxyError::InterpreterError(err) => err.code(),
+            RoxyError::EnvironmentError(err) => err.code(),
+            RoxyError::ResolutionError(err) => err.code(),
+            RoxyError::InternalError(err) => err.code(),
+            RoxyError::FileDoesNotExist => "E0600",
+        }
+    }
+
+    // Name of the variant, lets tools group errors without knowing every code
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RoxyError::SyntaxError(_) => "SyntaxError",
+            RoxyError::ParserError(_) => "ParserError",
+            RoxyError::InterpreterError(_) => "InterpreterError",
+            RoxyError::EnvironmentError(_) => "EnvironmentError",
+            RoxyError::ResolutionError(_) => "ResolutionError",
+            RoxyError::InternalError(_) => "InternalError",
+            RoxyError::FileDoesNotExist => "FileDoesNotExist",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            RoxyError::SyntaxError(err) => err.message.clone(),
+            RoxyError::ParserError(err) => err.message(),
+            RoxyError::InterpreterError(err) => err.message(),
+            RoxyError::EnvironmentError(err) => err.message(),
+            RoxyError::ResolutionError(err) => err.message(),
+            RoxyError::InternalError(err) => err.message(),
+            RoxyError::FileDoesNotExist => "file does not exist".into(),
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            RoxyError::SyntaxError(err) => Some(err.span),
+            RoxyError::ParserError(err) => err.token().map(|token| token.span),
+            RoxyError::InterpreterError(err) => err.token().map(|token| token.span),
+            RoxyError::EnvironmentError(_) => None,
+            RoxyError::ResolutionError(err) => Some(err.token().span),
+            RoxyError::InternalError(err) => Some(err.token().span),
+            RoxyError::FileDoesNotExist => None,
+        }
+    }
+
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            RoxyError::SyntaxError(_) => None,
+            RoxyError::ParserError(err) => err.help(),
+            RoxyError::InterpreterError(err) => err.help(),
+            RoxyError::ResolutionError(err) => err.help(),
+            _ => None,
+        }
+    }
+
+    pub fn is_runtime(&self) -> bool {
+        matches!(
+            self,
+            RoxyError::InterpreterError(_)
+                | RoxyError::EnvironmentError(_)
+                | RoxyError::InternalError(_)
+        )
+    }
+}
+
 impl std::fmt::Display for RoxyError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -27,6 +101,49 @@ impl std::fmt::Display for RoxyError {
     }
 }
 
+// Everything that went wrong while running a piece of source. The parser keeps going after an
+// error, so there can be more than one
+#[derive(Debug, Clone)]
+pub struct EvalError {
+    pub errors: Vec<RoxyError>,
+    // Functions a runtime error was raised in, innermost first. Empty when it was raised in
+    // top-level code
+    pub trace: Vec<StackFrame>,
+}
+
+impl std::error::Error for EvalError {}
+
+impl From<RoxyError> for EvalError {
+    fn from(err: RoxyError) -> Self {
+        Self {
+            errors: vec![err],
+            trace: vec![],
+        }
+    }
+}
+
+// A call which an error unwound through, `line` is where the function was called from. `file` is
+// where the function was declared, which is where the lines inside of it are
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub function: String,
+    pub file: Option<PathBuf>,
+    pub line: usize,
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        for (i, err) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "error[{}]: {}", err.code(), err.message())?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum InterpreterError {
     InvalidUnaryOperator(Token),
@@ -43,6 +160,204 @@ pub enum InterpreterError {
     ExpectedDotAfterSuper(Token),
     ExpectedSuperclassMethodName(Token),
     ExpectedRoxyClass,
+    UndefinedVariable(Token),
+    IndexOutOfBounds(f64, usize, Token),
+    IndexMustBeAnInteger(Token),
+    OnlyListsAndMapsCanBeIndexed(Token),
+    PopFromEmptyList(Token),
+    CantSortList(Token),
+    UndefinedKey(String, Token),
+    InvalidMapKey(Token),
+    ModuleNotFound(String, Token),
+    ImportCycle(String, Token),
+    ErrorInModule(String, String, Token),
+    // A value passed to `throw` which no `catch` handled
+    Thrown(RoxyType, Token),
+}
+
+impl InterpreterError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            InterpreterError::InvalidUnaryOperator(_) => "E0300",
+            InterpreterError::InvalidNumberCast(_) => "E0301",
+            InterpreterError::InvalidBooleanCast(_) => "E0302",
+            InterpreterError::InvalidStringCast(_) => "E0303",
+            InterpreterError::InvalidOperationOnGivenTypes(_) => "E0304",
+            InterpreterError::ExpectednArgsGotmArgs(..) => "E0305",
+            InterpreterError::DivideByZeroError(_) => "E0306",
+            InterpreterError::CanOnlyCallFunctionsAndClasses(_) => "E0307",
+            InterpreterError::OnlyInstancesHaveKeyword(..) => "E0308",
+            InterpreterError::UndefinedProperty(_) => "E0309",
+            InterpreterError::SuperclassMustBeAClass(_) => "E0310",
+            InterpreterError::ExpectedDotAfterSuper(_) => "E0311",
+            InterpreterError::ExpectedSuperclassMethodName(_) => "E0312",
+            InterpreterError::ExpectedRoxyClass => "E0313",
+            InterpreterError::UndefinedVariable(_) => "E0314",
+            InterpreterError::IndexOutOfBounds(..) => "E0315",
+            InterpreterError::IndexMustBeAnInteger(_) => "E0316",
+            InterpreterError::OnlyListsAndMapsCanBeIndexed(_) => "E0317",
+            InterpreterError::PopFromEmptyList(_) => "E0318",
+            InterpreterError::CantSortList(_) => "E0319",
+            InterpreterError::UndefinedKey(..) => "E0320",
+            InterpreterError::InvalidMapKey(_) => "E0321",
+            InterpreterError::ModuleNotFound(..) => "E0322",
+            InterpreterError::ImportCycle(..) => "E0323",
+            InterpreterError::ErrorInModule(..) => "E0324",
+            InterpreterError::Thrown(..) => "E0325",
+        }
+    }
+
+    // Name of the variant, it's the `kind` of the error object a `catch` gets
+    pub fn name(&self) -> &'static str {
+        match self {
+            InterpreterError::InvalidUnaryOperator(_) => "InvalidUnaryOperator",
+            InterpreterError::InvalidNumberCast(_) => "InvalidNumberCast",
+            InterpreterError::InvalidBooleanCast(_) => "InvalidBooleanCast",
+            InterpreterError::InvalidStringCast(_) => "InvalidStringCast",
+            InterpreterError::InvalidOperationOnGivenTypes(_) => "InvalidOperationOnGivenTypes",
+            InterpreterError::ExpectednArgsGotmArgs(..) => "ExpectednArgsGotmArgs",
+            InterpreterError::DivideByZeroError(_) => "DivideByZeroError",
+            InterpreterError::CanOnlyCallFunctionsAndClasses(_) => "CanOnlyCallFunctionsAndClasses",
+            InterpreterError::OnlyInstancesHaveKeyword(..) => "OnlyInstancesHaveKeyword",
+            InterpreterError::UndefinedProperty(_) => "UndefinedProperty",
+            InterpreterError::SuperclassMustBeAClass(_) => "SuperclassMustBeAClass",
+            InterpreterError::ExpectedDotAfterSuper(_) => "ExpectedDotAfterSuper",
+            InterpreterError::ExpectedSuperclassMethodName(_) => "ExpectedSuperclassMethodName",
+            InterpreterError::ExpectedRoxyClass => "ExpectedRoxyClass",
+            InterpreterError::UndefinedVariable(_) => "UndefinedVariable",
+            InterpreterError::IndexOutOfBounds(..) => "IndexOutOfBounds",
+            InterpreterError::IndexMustBeAnInteger(_) => "IndexMustBeAnInteger",
+            InterpreterError::OnlyListsAndMapsCanBeIndexed(_) => "OnlyListsAndMapsCanBeIndexed",
+            InterpreterError::PopFromEmptyList(_) => "PopFromEmptyList",
+            InterpreterError::CantSortList(_) => "CantSortList",
+            InterpreterError::UndefinedKey(..) => "UndefinedKey",
+            InterpreterError::InvalidMapKey(_) => "InvalidMapKey",
+            InterpreterError::ModuleNotFound(..) => "ModuleNotFound",
+            InterpreterError::ImportCycle(..) => "ImportCycle",
+            InterpreterError::ErrorInModule(..) => "ErrorInModule",
+            InterpreterError::Thrown(..) => "Thrown",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            InterpreterError::InvalidUnaryOperator(token) => {
+                format!("invalid unary operator `{}`", token.lexeme)
+            }
+            InterpreterError::InvalidNumberCast(token) => {
+                format!("operands of `{}` must be numbers", token.lexeme)
+            }
+            InterpreterError::InvalidBooleanCast(token) => {
+                format!("operand of `{}` must be a boolean", token.lexeme)
+            }
+            InterpreterError::InvalidStringCast(token) => {
+                format!("operand of `{}` must be a string", token.lexeme)
+            }
+            InterpreterError::InvalidOperationOnGivenTypes(token) => {
+                format!("invalid operand types for `{}`", token.lexeme)
+            }
+            InterpreterError::ExpectednArgsGotmArgs(n, m, _) => {
+                format!("expected {} arguments but got {}", n, m)
+            }
+            InterpreterError::DivideByZeroError(_) => "division by zero".into(),
+            InterpreterError::CanOnlyCallFunctionsAndClasses(_) => {
+                "can only call functions and classes".into()
+            }
+            InterpreterError::OnlyInstancesHaveKeyword(keyword, _) => {
+                format!("only instances have {}", keyword)
+            }
+            InterpreterError::UndefinedProperty(token) => {
+                format!("undefined property `{}`", token.lexeme)
+            }
+            InterpreterError::SuperclassMustBeAClass(_) => "superclass must be a class".into(),
+            InterpreterError::ExpectedDotAfterSuper(_) => "expected `.` after `super`".into(),
+            InterpreterError::ExpectedSuperclassMethodName(_) => {
+                "expected superclass method name".into()
+            }
+            InterpreterError::ExpectedRoxyClass => "expected a class".into(),
+            InterpreterError::UndefinedVariable(token) => {
+                format!("undefined variable `{}`", token.lexeme)
+            }
+            InterpreterError::IndexOutOfBounds(index, len, _) => {
+                format!("index {} out of bounds for list of length {}", index, len)
+            }
+            InterpreterError::IndexMustBeAnInteger(_) => "index must be an integer".into(),
+            InterpreterError::OnlyListsAndMapsCanBeIndexed(_) => {
+                "only lists and maps can be indexed".into()
+            }
+            InterpreterError::PopFromEmptyList(_) => "can't pop from an empty list".into(),
+            InterpreterError::CantSortList(_) => {
+                "can only sort a list of numbers or a list of strings".into()
+            }
+            InterpreterError::UndefinedKey(key, _) => format!("undefined key {}", key),
+            InterpreterError::InvalidMapKey(_) => {
+                "map keys must be strings, numbers, booleans or nil".into()
+            }
+            InterpreterError::ModuleNotFound(path, _) => format!("can't read module `{}`", path),
+            InterpreterError::ImportCycle(cycle, _) => format!("import cycle: {}", cycle),
+            InterpreterError::ErrorInModule(module, error, _) => {
+                format!("in module `{}`: {}", module, error)
+            }
+            InterpreterError::Thrown(value, _) => format!("uncaught {}", describe_thrown(value)),
+        }
+    }
+
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            InterpreterError::InvalidUnaryOperator(token)
+            | InterpreterError::InvalidNumberCast(token)
+            | InterpreterError::InvalidBooleanCast(token)
+            | InterpreterError::InvalidStringCast(token)
+            | InterpreterError::InvalidOperationOnGivenTypes(token)
+            | InterpreterError::ExpectednArgsGotmArgs(_, _, token)
+            | InterpreterError::DivideByZeroError(token)
+            | InterpreterError::CanOnlyCallFunctionsAndClasses(token)
+            | InterpreterError::OnlyInstancesHaveKeyword(_, token)
+            | InterpreterError::UndefinedProperty(token)
+            | InterpreterError::SuperclassMustBeAClass(token)
+            | InterpreterError::ExpectedDotAfterSuper(token)
+            | InterpreterError::ExpectedSuperclassMethodName(token)
+            | InterpreterError::UndefinedVariable(token)
+            | InterpreterError::IndexOutOfBounds(_, _, token)
+            | InterpreterError::IndexMustBeAnInteger(token)
+            | InterpreterError::OnlyListsAndMapsCanBeIndexed(token)
+            | InterpreterError::PopFromEmptyList(token)
+            | InterpreterError::CantSortList(token)
+            | InterpreterError::UndefinedKey(_, token)
+            | InterpreterError::InvalidMapKey(token)
+            | InterpreterError::ModuleNotFound(_, token)
+            | InterpreterError::ImportCycle(_, token)
+            | InterpreterError::ErrorInModule(_, _, token)
+            | InterpreterError::Thrown(_, token) => Some(token),
+            InterpreterError::ExpectedRoxyClass => None,
+        }
+    }
+
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            InterpreterError::InvalidOperationOnGivenTypes(_) => {
+                Some("`+` works on two numbers or two strings")
+            }
+            InterpreterError::DivideByZeroError(_) => Some("check the divisor before dividing"),
+            InterpreterError::CanOnlyCallFunctionsAndClasses(_) => {
+                Some("only functions, methods and classes can be followed by `(...)`")
+            }
+            InterpreterError::UndefinedVariable(_) => {
+                Some("declare the variable with `var` before using it")
+            }
+            InterpreterError::IndexOutOfBounds(..) => {
+                Some("lists are indexed from 0 up to `len() - 1`")
+            }
+            InterpreterError::UndefinedKey(..) => Some("check for the key with `has` first"),
+            InterpreterError::ModuleNotFound(..) => {
+                Some("module paths are relative to the file containing the import")
+            }
+            InterpreterError::Thrown(..) => {
+                Some("handle it with `try { ... } catch (e) { ... }` around the code that throws")
+            }
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for InterpreterError {
@@ -138,8 +453,83 @@ impl std::fmt::Display for InterpreterError {
             InterpreterError::ExpectedRoxyClass => {
                 write!(f, "InterpreterError: Expected roxy class")
             }
+            InterpreterError::UndefinedVariable(token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Undefined variable: {:?}",
+                token.line, token.lexeme
+            ),
+            InterpreterError::IndexOutOfBounds(index, len, token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Index {} out of bounds for length {}",
+                token.line, index, len
+            ),
+            InterpreterError::IndexMustBeAnInteger(token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Index must be an integer",
+                token.line
+            ),
+            InterpreterError::OnlyListsAndMapsCanBeIndexed(token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Only lists and maps can be indexed",
+                token.line
+            ),
+            InterpreterError::PopFromEmptyList(token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Can't pop from an empty list",
+                token.line
+            ),
+            InterpreterError::CantSortList(token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Can only sort numbers or strings",
+                token.line
+            ),
+            InterpreterError::UndefinedKey(key, token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Undefined key: {}",
+                token.line, key
+            ),
+            InterpreterError::InvalidMapKey(token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Invalid map key",
+                token.line
+            ),
+            InterpreterError::ModuleNotFound(path, token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Module not found: {:?}",
+                token.line, path
+            ),
+            InterpreterError::ImportCycle(cycle, token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Import cycle: {}",
+                token.line, cycle
+            ),
+            InterpreterError::ErrorInModule(module, error, token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: In module {:?}: {}",
+                token.line, module, error
+            ),
+            InterpreterError::Thrown(value, token) => write!(
+                f,
+                "[line: {:?}] InterpreterError: Uncaught {}",
+                token.line,
+                describe_thrown(value)
+            ),
+        }
+    }
+}
+
+// Error objects read as `Kind: message`, anything else that got thrown is printed as is
+fn describe_thrown(value: &RoxyType) -> String {
+    if let RoxyType::RoxyInstance(instance) = value {
+        let instance = instance.borrow();
+        if let (Some(kind), Some(message)) =
+            (instance.fields.get("kind"), instance.fields.get("message"))
+        {
+            return format!("{}: {}", kind, message);
         }
     }
+
+    return format!("exception: {}", value);
 }
 
 #[derive(Debug, Clone)]
@@ -147,6 +537,7 @@ pub struct CompileTimeError {
     pub line: usize,
     pub where_in_file: String, //Just because where is a reserved keyword in rust
     pub message: String,
+    pub span: Span,
 }
 
 impl std::fmt::Display for CompileTimeError {
@@ -178,6 +569,117 @@ pub enum ParserError {
     ExpectedSemicolonAfterClauses(Token),
     InvalidAssignmentTarget(Token),
     CannotHaveMoreThan255Arguments(Token),
+    ExpectedRightBracket(Token),
+    ExpectedModulePath(Token),
+    ExpectedCatchOrFinally(Token),
+    ExpectedInterpolationEnd(Token),
+}
+
+impl ParserError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParserError::InvalidPeek => "E0100",
+            ParserError::InvalidTokenAccess(_) => "E0101",
+            ParserError::InvalidToken(_) => "E0102",
+            ParserError::ExpectedLeftParen(_) => "E0103",
+            ParserError::ExpectedRightParen(_) => "E0104",
+            ParserError::ExpectedExpression(_) => "E0105",
+            ParserError::ExpectedSemicolon(_) => "E0106",
+            ParserError::ExpectedIdentifier(..) => "E0107",
+            ParserError::ExpectedSuperclassName(_) => "E0108",
+            ParserError::ExpectedVariableName(_) => "E0109",
+            ParserError::ExpectedParameterName(_) => "E0110",
+            ParserError::ExpectedRightBraceAfterBlock(_) => "E0111",
+            ParserError::ExpectedPunctAfterKeyword(..) => "E0112",
+            ParserError::ExpectedSemicolonAfterClauses(_) => "E0113",
+            ParserError::InvalidAssignmentTarget(_) => "E0114",
+            ParserError::CannotHaveMoreThan255Arguments(_) => "E0115",
+            ParserError::ExpectedRightBracket(_) => "E0116",
+            ParserError::ExpectedModulePath(_) => "E0117",
+            ParserError::ExpectedCatchOrFinally(_) => "E0118",
+            ParserError::ExpectedInterpolationEnd(_) => "E0119",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ParserError::InvalidPeek => "internal parser error: invalid peek of token".into(),
+            ParserError::InvalidTokenAccess(_) => {
+                "internal parser error: invalid token access".into()
+            }
+            ParserError::InvalidToken(token) => format!("invalid token `{}`", token.lexeme),
+            ParserError::ExpectedLeftParen(_) => "expected `(`".into(),
+            ParserError::ExpectedRightParen(_) => "expected `)`".into(),
+            ParserError::ExpectedExpression(_) => "expected expression".into(),
+            ParserError::ExpectedSemicolon(_) => "expected `;`".into(),
+            ParserError::ExpectedIdentifier(kind, _, _) => {
+                format!("expected identifier for {}", kind)
+            }
+            ParserError::ExpectedSuperclassName(_) => "expected superclass name".into(),
+            ParserError::ExpectedVariableName(_) => "expected variable name".into(),
+            ParserError::ExpectedParameterName(_) => "expected parameter name".into(),
+            ParserError::ExpectedRightBraceAfterBlock(_) => "expected `}` after block".into(),
+            ParserError::ExpectedPunctAfterKeyword(punctuation, keyword, _) => {
+                format!("expected `{}` after {}", punctuation, keyword)
+            }
+            ParserError::ExpectedSemicolonAfterClauses(_) => {
+                "expected `)` after for clauses".into()
+            }
+            ParserError::InvalidAssignmentTarget(_) => "invalid assignment target".into(),
+            ParserError::CannotHaveMoreThan255Arguments(_) => {
+                "can't have more than 255 arguments".into()
+            }
+            ParserError::ExpectedRightBracket(_) => "expected `]`".into(),
+            ParserError::ExpectedModulePath(token) => {
+                format!("expected module path after `{}`", token.lexeme)
+            }
+            ParserError::ExpectedCatchOrFinally(_) => {
+                "expected `catch` or `finally` after try block".into()
+            }
+            ParserError::ExpectedInterpolationEnd(_) => {
+                "expected `}` after interpolated expression".into()
+            }
+        }
+    }
+
+    pub fn token(&self) -> Option<&Token> {
+        match self {
+            ParserError::InvalidPeek => None,
+            ParserError::InvalidTokenAccess(token)
+            | ParserError::InvalidToken(token)
+            | ParserError::ExpectedLeftParen(token)
+            | ParserError::ExpectedRightParen(token)
+            | ParserError::ExpectedExpression(token)
+            | ParserError::ExpectedSemicolon(token)
+            | ParserError::ExpectedIdentifier(_, _, token)
+            | ParserError::ExpectedSuperclassName(token)
+            | ParserError::ExpectedVariableName(token)
+            | ParserError::ExpectedParameterName(token)
+            | ParserError::ExpectedRightBraceAfterBlock(token)
+            | ParserError::ExpectedPunctAfterKeyword(_, _, token)
+            | ParserError::ExpectedSemicolonAfterClauses(token)
+            | ParserError::InvalidAssignmentTarget(token)
+            | ParserError::CannotHaveMoreThan255Arguments(token)
+            | ParserError::ExpectedRightBracket(token)
+            | ParserError::ExpectedModulePath(token)
+            | ParserError::ExpectedCatchOrFinally(token)
+            | ParserError::ExpectedInterpolationEnd(token) => Some(token),
+        }
+    }
+
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            ParserError::ExpectedSemicolon(_) => Some("statements have to end with a `;`"),
+            ParserError::InvalidAssignmentTarget(_) => {
+                Some("only variables, fields and list elements can be assigned to")
+            }
+            ParserError::ExpectedModulePath(_) => Some("module paths are strings like \"math.rx\""),
+            ParserError::ExpectedCatchOrFinally(_) => {
+                Some("a `try` needs a `catch (e) { ... }`, a `finally { ... }` or both")
+            }
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for ParserError {
@@ -263,6 +765,26 @@ impl std::fmt::Display for ParserError {
                 "[line: {:?}] ParserError: Cannot have more than 255 arguments: {:?}",
                 token.line, token.lexeme
             ),
+            ParserError::ExpectedRightBracket(token) => write!(
+                f,
+                "[line: {:?}] ParserError: Expected right bracket: {:?}",
+                token.line, token.lexeme
+            ),
+            ParserError::ExpectedModulePath(token) => write!(
+                f,
+                "[line: {:?}] ParserError: Expected module path: {:?}",
+                token.line, token.lexeme
+            ),
+            ParserError::ExpectedCatchOrFinally(token) => write!(
+                f,
+                "[line: {:?}] ParserError: Expected catch or finally: {:?}",
+                token.line, token.lexeme
+            ),
+            ParserError::ExpectedInterpolationEnd(token) => write!(
+                f,
+                "[line: {:?}] ParserError: Expected end of interpolation: {:?}",
+                token.line, token.lexeme
+            ),
         }
     }
 }
@@ -273,6 +795,26 @@ pub enum EnvironmentError {
     EnvironmentDoesNotExistAtGivenDistance,
 }
 
+impl EnvironmentError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EnvironmentError::UndefinedVariable(_) => "E0400",
+            EnvironmentError::EnvironmentDoesNotExistAtGivenDistance => "E0401",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            EnvironmentError::UndefinedVariable(var_name) => {
+                format!("undefined variable `{}`", var_name)
+            }
+            EnvironmentError::EnvironmentDoesNotExistAtGivenDistance => {
+                "internal error: environment does not exist at given distance".into()
+            }
+        }
+    }
+}
+
 impl std::fmt::Display for EnvironmentError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -300,6 +842,105 @@ pub enum ResolutionError {
     AClassCantInheritFromItself(Token),
     CantUseSuperOutsideClass(Token),
     CantUseSuperInAClassWithNoSuperclass(Token),
+    CantBreakOutsideOfALoop(Token),
+    CantContinueOutsideOfALoop(Token),
+    ImportOutsideOfTopLevel(Token),
+    UndefinedImport(String, Token),
+}
+
+impl ResolutionError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ResolutionError::CantReadLocalVariableInItsOwnInitializer(_) => "E0200",
+            ResolutionError::InvalidScopeAccess(_) => "E0201",
+            ResolutionError::AlreadyAVariableWithThisNameInThisScope(_) => "E0202",
+            ResolutionError::CantReturnFromTopLevelCode(_) => "E0203",
+            ResolutionError::CantUseThisOutsideOfAClass(_) => "E0204",
+            ResolutionError::CantReturnAValueFromAnInitializer(_) => "E0205",
+            ResolutionError::AClassCantInheritFromItself(_) => "E0206",
+            ResolutionError::CantUseSuperOutsideClass(_) => "E0207",
+            ResolutionError::CantUseSuperInAClassWithNoSuperclass(_) => "E0208",
+            ResolutionError::CantBreakOutsideOfALoop(_) => "E0209",
+            ResolutionError::CantContinueOutsideOfALoop(_) => "E0210",
+            ResolutionError::ImportOutsideOfTopLevel(_) => "E0211",
+            ResolutionError::UndefinedImport(..) => "E0212",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ResolutionError::CantReadLocalVariableInItsOwnInitializer(token) => format!(
+                "can't read local variable `{}` in its own initializer",
+                token.lexeme
+            ),
+            ResolutionError::InvalidScopeAccess(_) => "internal error: invalid scope access".into(),
+            ResolutionError::AlreadyAVariableWithThisNameInThisScope(token) => format!(
+                "a variable named `{}` already exists in this scope",
+                token.lexeme
+            ),
+            ResolutionError::CantReturnFromTopLevelCode(_) => {
+                "can't return from top-level code".into()
+            }
+            ResolutionError::CantUseThisOutsideOfAClass(_) => {
+                "can't use `this` outside of a class".into()
+            }
+            ResolutionError::CantReturnAValueFromAnInitializer(_) => {
+                "can't return a value from an initializer".into()
+            }
+            ResolutionError::AClassCantInheritFromItself(_) => {
+                "a class can't inherit from itself".into()
+            }
+            ResolutionError::CantUseSuperOutsideClass(_) => {
+                "can't use `super` outside of a class".into()
+            }
+            ResolutionError::CantUseSuperInAClassWithNoSuperclass(_) => {
+                "can't use `super` in a class with no superclass".into()
+            }
+            ResolutionError::CantBreakOutsideOfALoop(_) => "can't `break` outside of a loop".into(),
+            ResolutionError::CantContinueOutsideOfALoop(_) => {
+                "can't `continue` outside of a loop".into()
+            }
+            ResolutionError::ImportOutsideOfTopLevel(_) => {
+                "imports are only allowed at the top level of a file".into()
+            }
+            ResolutionError::UndefinedImport(module, token) => {
+                format!("module `{}` has no top-level `{}`", module, token.lexeme)
+            }
+        }
+    }
+
+    pub fn token(&self) -> &Token {
+        match self {
+            ResolutionError::CantReadLocalVariableInItsOwnInitializer(token)
+            | ResolutionError::InvalidScopeAccess(token)
+            | ResolutionError::AlreadyAVariableWithThisNameInThisScope(token)
+            | ResolutionError::CantReturnFromTopLevelCode(token)
+            | ResolutionError::CantUseThisOutsideOfAClass(token)
+            | ResolutionError::CantReturnAValueFromAnInitializer(token)
+            | ResolutionError::AClassCantInheritFromItself(token)
+            | ResolutionError::CantUseSuperOutsideClass(token)
+            | ResolutionError::CantUseSuperInAClassWithNoSuperclass(token)
+            | ResolutionError::CantBreakOutsideOfALoop(token)
+            | ResolutionError::CantContinueOutsideOfALoop(token)
+            | ResolutionError::ImportOutsideOfTopLevel(token)
+            | ResolutionError::UndefinedImport(_, token) => token,
+        }
+    }
+
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            ResolutionError::AlreadyAVariableWithThisNameInThisScope(_) => {
+                Some("pick a different name or assign to the existing variable")
+            }
+            ResolutionError::CantReturnAValueFromAnInitializer(_) => {
+                Some("initializers always return `this`, use a bare `return;` instead")
+            }
+            ResolutionError::UndefinedImport(..) => Some(
+                "only functions, classes and variables declared at the top level can be imported",
+            ),
+            _ => None,
+        }
+    }
 }
 
 impl std::fmt::Display for ResolutionError {
@@ -368,6 +1009,34 @@ impl std::fmt::Display for ResolutionError {
                     token.line, token.lexeme
                 )
             }
+            ResolutionError::CantBreakOutsideOfALoop(token) => {
+                write!(
+                    f,
+                    "[line: {:?}] ResolutionError: Can't break outside of a loop: {:?}",
+                    token.line, token.lexeme
+                )
+            }
+            ResolutionError::CantContinueOutsideOfALoop(token) => {
+                write!(
+                    f,
+                    "[line: {:?}] ResolutionError: Can't continue outside of a loop: {:?}",
+                    token.line, token.lexeme
+                )
+            }
+            ResolutionError::ImportOutsideOfTopLevel(token) => {
+                write!(
+                    f,
+                    "[line: {:?}] ResolutionError: Import outside of top level: {:?}",
+                    token.line, token.lexeme
+                )
+            }
+            ResolutionError::UndefinedImport(module, token) => {
+                write!(
+                    f,
+                    "[line: {:?}] ResolutionError: Module {:?} has no top-level {:?}",
+                    token.line, module, token.lexeme
+                )
+            }
         }
     }
 }
@@ -377,6 +1046,28 @@ pub enum InternalError {
     TimeConversionError(Token),
 }
 
+impl InternalError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            InternalError::TimeConversionError(_) => "E0500",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            InternalError::TimeConversionError(_) => {
+                "internal error: time conversion failed".into()
+            }
+        }
+    }
+
+    pub fn token(&self) -> &Token {
+        match self {
+            InternalError::TimeConversionError(token) => token,
+        }
+    }
+}
+
 impl std::fmt::Display for InternalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         match self {
@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use crate::{
+    expr::Stmt,
+    interpreter::Interpreter,
+    parser::Parser,
+    resolver::Resolver,
+    scanner::Scanner,
+    tokens::TokenType,
+    utils::errors::{EvalError, InterpreterError, RoxyError},
+    RoxyType, Token,
+};
+
+pub type Value = RoxyType;
+pub type Error = EvalError;
+
+// Entry point for embedding Lox in a Rust program. A `Vm` owns the whole
+// scanner -> parser -> resolver -> interpreter pipeline and keeps globals alive between calls:
+//
+// let mut vm = Vm::new();
+// vm.eval("fun add(a, b) { return a + b; }")?;
+// let sum = vm.call_function("add", vec![1.into(), 2.into()])?;
+#[derive(Debug, Default)]
+pub struct Vm {
+    interpreter: Interpreter,
+}
+
+impl Vm {
+    pub fn new() -> Self {
+        Self {
+            interpreter: Interpreter::new(),
+        }
+    }
+
+    pub fn interpreter(&self) -> &Interpreter {
+        &self.interpreter
+    }
+
+    pub fn parse(source: &str) -> Result<Vec<Stmt>, Error> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens()?;
+
+        let mut parser = Parser::new(tokens.clone());
+        let stmts = parser.parse()?;
+        if !parser.errors.is_empty() {
+            return Err(EvalError {
+                errors: parser.errors,
+                trace: vec![],
+            });
+        }
+
+        Ok(stmts)
+    }
+
+    // Imports in code run afterwards are resolved relative to this file
+    pub fn set_file<P: AsRef<Path>>(&mut self, path: P) {
+        self.interpreter.set_current_file(path.as_ref());
+    }
+
+    // Runs the source as statements, like a file passed to the binary
+    pub fn run(&mut self, source: &str) -> Result<(), Error> {
+        let stmts = Vm::parse(source)?;
+
+        Resolver::new(&mut self.interpreter).resolve(stmts.clone())?;
+        for stmt in stmts {
+            self.interpreter
+                .interpret(stmt)
+                .map_err(|err| self.runtime_error(err))?;
+        }
+
+        Ok(())
+    }
+
+    // Evaluates the source when it is a lone expression like `1 + 2`. None is returned without
+    // running anything when it is not, the source should be run as statements then
+    pub fn eval_expression(&mut self, source: &str) -> Result<Option<Value>, Error> {
+        let mut scanner = Scanner::new(source.to_string());
+        let tokens = scanner.scan_tokens()?;
+
+        let mut parser = Parser::new(tokens.clone());
+        let expr = match parser.parse_expression() {
+            Ok(Some(expr)) if parser.errors.is_empty() => expr,
+            // Running it as statements reports the errors
+            _ => return Ok(None),
+        };
+
+        Resolver::new(&mut self.interpreter).resolve_expr(expr.clone())?;
+        let value = self
+            .interpreter
+            .evaluate(&expr)
+            .map_err(|err| self.runtime_error(err))?;
+        return Ok(Some(value));
+    }
+
+    // Value of the source if it is a lone expression, otherwise it is run as statements and nil
+    // is returned
+    pub fn eval(&mut self, source: &str) -> Result<Value, Error> {
+        if let Some(value) = self.eval_expression(source)? {
+            return Ok(value);
+        }
+
+        self.run(source)?;
+        Ok(RoxyType::NULL)
+    }
+
+    pub fn call_function(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, Error> {
+        let token = Token::synthetic(TokenType::Identifier, name);
+        let callee = match self.get_global(name) {
+            Some(callee) => callee,
+            None => {
+                return Err(
+                    RoxyError::InterpreterError(InterpreterError::UndefinedVariable(token)).into(),
+                )
+            }
+        };
+
+        return self
+            .interpreter
+            .call_value(&callee, arguments, &token)
+            .map_err(|err| self.runtime_error(err));
+    }
+
+    // Runtime errors come with the calls they unwound through
+    fn runtime_error(&mut self, err: RoxyError) -> Error {
+        return EvalError {
+            errors: vec![err],
+            trace: self.interpreter.take_trace(),
+        };
+    }
+
+    // Natives included
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        return self.interpreter.globals.borrow().get(name.to_string()).ok();
+    }
+
+    // Defines the global when it does not exist yet
+    pub fn set_global<T: Into<Value>>(&mut self, name: &str, value: T) {
+        self.interpreter
+            .globals
+            .borrow_mut()
+            .define(name.to_string(), value.into());
+    }
+
+    pub fn register_native<F>(&mut self, name: &str, arity: usize, callable: F)
+    where
+        F: Fn(&mut Interpreter, Vec<Value>, Token) -> Result<Value, RoxyError> + 'static,
+    {
+        self.interpreter.register_native(name, arity, callable);
+    }
+}
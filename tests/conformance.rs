@@ -0,0 +1,134 @@
+// Truthiness and equality have to match the Lox spec, everything else builds on top of them
+use roxy::vm::{Value, Vm};
+
+fn eval(source: &str) -> Value {
+    let mut vm = Vm::new();
+    vm.eval(source).unwrap()
+}
+
+// Value of `result` after running the statements
+fn run(source: &str) -> Value {
+    let mut vm = Vm::new();
+    vm.run(source).unwrap();
+    vm.get_global("result").unwrap()
+}
+
+fn assert_bool(source: &str, expected: bool) {
+    assert_eq!(eval(source), Value::Boolean(expected), "{}", source);
+}
+
+const TRUTHY: &[&str] = &[
+    "true",
+    "0",
+    "1",
+    "-1",
+    "\"\"",
+    "\"false\"",
+    "clock",
+    "Foo",
+    "Foo()",
+    "f",
+];
+const FALSY: &[&str] = &["nil", "false"];
+const PRELUDE: &str = "class Foo {} fun f() {}";
+
+#[test]
+fn only_nil_and_false_are_falsy() {
+    for (values, expected) in [(TRUTHY, true), (FALSY, false)] {
+        for value in values {
+            let bang = format!("{} var result = !{};", PRELUDE, value);
+            assert_eq!(run(&bang), Value::Boolean(!expected), "!{}", value);
+
+            let if_stmt = format!(
+                "{} var result; if ({}) result = true; else result = false;",
+                PRELUDE, value
+            );
+            assert_eq!(run(&if_stmt), Value::Boolean(expected), "if ({})", value);
+
+            let while_stmt = format!(
+                "{} var result = false; while ({}) {{ result = true; break; }}",
+                PRELUDE, value
+            );
+            assert_eq!(
+                run(&while_stmt),
+                Value::Boolean(expected),
+                "while ({})",
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn logical_operators_use_truthiness() {
+    assert_eq!(eval("0 or \"fallback\""), Value::Number(0.0));
+    assert_eq!(
+        eval("nil or \"fallback\""),
+        Value::String("fallback".into())
+    );
+    assert_eq!(eval("false or nil"), Value::NULL);
+    assert_eq!(eval("\"\" and 2"), Value::Number(2.0));
+    assert_eq!(eval("nil and 2"), Value::NULL);
+    assert_eq!(eval("false and 2"), Value::Boolean(false));
+}
+
+#[test]
+fn nil_is_only_equal_to_nil() {
+    assert_bool("nil == nil", true);
+    assert_bool("nil == false", false);
+    assert_bool("false == nil", false);
+    assert_bool("nil == true", false);
+    assert_bool("true == nil", false);
+    assert_bool("nil == 0", false);
+    assert_bool("nil == \"\"", false);
+    assert_bool("nil != false", true);
+}
+
+#[test]
+fn values_compare_by_value() {
+    assert_bool("1 == 1", true);
+    assert_bool("1 == 2", false);
+    assert_bool("\"a\" == \"a\"", true);
+    assert_bool("\"a\" == \"b\"", false);
+    assert_bool("true == true", true);
+    assert_bool("true == false", false);
+    assert_bool("true != false", true);
+}
+
+#[test]
+fn different_types_are_never_equal() {
+    assert_bool("1 == \"1\"", false);
+    assert_bool("0 == false", false);
+    assert_bool("\"\" == false", false);
+    assert_bool("1 == true", false);
+    assert_bool("\"true\" == true", false);
+}
+
+#[test]
+fn objects_compare_by_identity() {
+    let cases = [
+        ("var a = Foo(); var result = a == a;", true),
+        ("var a = Foo(); var b = a; var result = a == b;", true),
+        ("var result = Foo() == Foo();", false),
+        ("var result = Foo == Foo;", true),
+        ("class Bar {} var result = Foo == Bar;", false),
+        ("var result = f == f;", true),
+        ("fun g() {} var result = f == g;", false),
+        ("var result = clock == clock;", true),
+        ("var result = Foo() != Foo();", true),
+    ];
+
+    for (source, expected) in cases {
+        let source = format!("{} {}", PRELUDE, source);
+        assert_eq!(run(&source), Value::Boolean(expected), "{}", source);
+    }
+}
+
+#[test]
+fn instances_with_equal_fields_are_still_different() {
+    let source = "
+        class Point { init(x) { this.x = x; } }
+        var result = Point(1) == Point(1);
+    ";
+    assert_eq!(run(source), Value::Boolean(false));
+}
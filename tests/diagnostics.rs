@@ -0,0 +1,178 @@
+// The human error format is compared exactly, it is what people read in their terminal
+use std::path::Path;
+use std::process::Command;
+
+use roxy::utils::diagnostics::DiagnosticRenderer;
+use roxy::vm::Vm;
+
+fn render(source: &str) -> String {
+    let err = Vm::new().run(source).unwrap_err();
+    let renderer = DiagnosticRenderer::new("test.rx", source).with_color(false);
+    err.errors.iter().map(|err| renderer.render(err)).collect()
+}
+
+#[test]
+fn caret_underlines_the_span() {
+    assert_eq!(
+        render("print 1 / 0;"),
+        "\
+error[E0306]: division by zero
+ --> test.rx:1:9
+  |
+1 | print 1 / 0;
+  |         ^
+  = help: check the divisor before dividing
+"
+    );
+
+    assert_eq!(
+        render("var = 2;"),
+        "\
+error[E0109]: expected variable name
+ --> test.rx:1:1
+  |
+1 | var = 2;
+  | ^^^
+"
+    );
+}
+
+#[test]
+fn spans_over_several_lines_are_clipped_to_the_first() {
+    assert_eq!(
+        render("var x = 1;\nprint \"abc\ndef"),
+        "\
+error[E0001]: unterminated string
+ --> test.rx:2:7
+  |
+2 | print \"abc
+  |       ^^^^
+"
+    );
+}
+
+#[test]
+fn gutter_is_as_wide_as_the_line_number() {
+    assert_eq!(
+        render(&format!("{}print nope;", "\n".repeat(9))),
+        "\
+error[E0314]: undefined variable `nope`
+  --> test.rx:10:7
+   |
+10 | print nope;
+   |       ^^^^
+   = help: declare the variable with `var` before using it
+"
+    );
+}
+
+#[test]
+fn every_error_is_rendered() {
+    assert_eq!(
+        render("print 1 +;\nvar = 2;"),
+        "\
+error[E0105]: expected expression
+ --> test.rx:1:10
+  |
+1 | print 1 +;
+  |          ^
+error[E0109]: expected variable name
+ --> test.rx:2:1
+  |
+2 | var = 2;
+  | ^^^
+"
+    );
+}
+
+#[test]
+fn errors_without_a_location_only_name_the_file() {
+    let err = Vm::new().call_function("nope", vec![]).unwrap_err();
+    let renderer = DiagnosticRenderer::new("test.rx", "").with_color(false);
+    assert_eq!(
+        renderer.render(&err.errors[0]),
+        "\
+error[E0314]: undefined variable `nope`
+--> test.rx
+ = help: declare the variable with `var` before using it
+"
+    );
+}
+
+#[test]
+fn color_is_ansi_escapes_around_each_part() {
+    let source = "print 1 / 0;";
+    let err = Vm::new().run(source).unwrap_err();
+
+    let colored = DiagnosticRenderer::new("test.rx", source).render(&err.errors[0]);
+    assert_eq!(
+        colored,
+        "\x1b[1;31merror[E0306]\x1b[0m: \x1b[1mdivision by zero\x1b[0m\n \
+         \x1b[1;34m-->\x1b[0m test.rx:1:9\n  \
+         \x1b[1;34m|\x1b[0m\n\
+         \x1b[1;34m1\x1b[0m \x1b[1;34m|\x1b[0m print 1 / 0;\n  \
+         \x1b[1;34m|\x1b[0m         \x1b[1;31m^\x1b[0m\n  \
+         \x1b[1;34m=\x1b[0m \x1b[1mhelp: check the divisor before dividing\x1b[0m\n"
+    );
+
+    let plain = DiagnosticRenderer::new("test.rx", source)
+        .with_color(false)
+        .render(&err.errors[0]);
+    assert!(!plain.contains('\x1b'));
+}
+
+// What the binary prints for a program in `tests/traces/`, run from that directory
+fn stderr_of(program: &str) -> String {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("traces");
+    let output = Command::new(env!("CARGO_BIN_EXE_roxy"))
+        .arg("--no-color")
+        .arg(program)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(70));
+
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn uncaught_errors_print_the_calls_they_unwound_through() {
+    assert_eq!(
+        stderr_of("nested.rx"),
+        "\
+error[E0325]: uncaught Error: bad 2
+ --> nested.rx:3:3
+  |
+3 |   throw Error(\"bad ${x}\");
+  |   ^^^^^
+  = help: handle it with `try { ... } catch (e) { ... }` around the code that throws
+stack trace:
+  in inner() at nested.rx:3
+  in middle() at nested.rx:7
+  in outer() at nested.rx:11
+  in script at nested.rx:14
+"
+    );
+}
+
+#[test]
+fn frames_in_other_modules_point_into_their_file() {
+    assert_eq!(
+        stderr_of("from_module.rx"),
+        "\
+error[E0325]: uncaught Error: bad 1
+ --> lib/thrower.rx:3:3
+  |
+3 |   throw Error(\"bad ${x}\");
+  |   ^^^^^
+  = help: handle it with `try { ... } catch (e) { ... }` around the code that throws
+stack trace:
+  in fail() at lib/thrower.rx:3
+  in callsFail() at lib/thrower.rx:7
+  in outer() at from_module.rx:5
+  in script at from_module.rx:8
+"
+    );
+}
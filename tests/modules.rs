@@ -0,0 +1,29 @@
+// Imports are checked and run against one read of the module, whatever happens to the file after
+#![allow(clippy::result_large_err)]
+
+use std::fs;
+
+use roxy::vm::{Value, Vm};
+
+#[test]
+fn a_module_is_read_once_for_checking_and_running() {
+    let dir = std::env::temp_dir().join(format!("roxy-modules-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let module = dir.join("changing.rx");
+    fs::write(&module, "var value = \"first\";").unwrap();
+
+    let mut vm = Vm::new();
+    vm.set_file(dir.join("main.rx"));
+    let rewritten = module.clone();
+    vm.register_native("rewrite", 0, move |_, _, _| {
+        fs::write(&rewritten, "var other = \"second\";").unwrap();
+        Ok(Value::NULL)
+    });
+
+    // The import is checked before anything runs, the file changes before the import runs
+    let result = vm.run("rewrite(); from \"changing.rx\" import value; var seen = value;");
+    fs::remove_dir_all(&dir).unwrap();
+
+    result.unwrap();
+    assert_eq!(vm.get_global("seen"), Some(Value::from("first")));
+}
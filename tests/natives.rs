@@ -0,0 +1,55 @@
+// Natives are Rust closures, the state they capture stays with the host
+#![allow(clippy::result_large_err)]
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use roxy::vm::{Value, Vm};
+
+#[test]
+fn closures_keep_the_host_state_they_capture() {
+    let mut vm = Vm::new();
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    vm.register_native("tick", 0, move |_, _, _| {
+        counter.set(counter.get() + 1);
+        Ok(Value::Number(counter.get() as f64))
+    });
+
+    let seen = Rc::new(RefCell::new(vec![]));
+    let log = seen.clone();
+    vm.register_native("log", 1, move |_, args, _| {
+        log.borrow_mut().push(args[0].to_string());
+        Ok(Value::NULL)
+    });
+
+    vm.run("for (var i = 0; i < 3; i = i + 1) log(\"tick ${tick()}\");")
+        .unwrap();
+    assert_eq!(calls.get(), 3);
+    assert_eq!(*seen.borrow(), vec!["tick 1", "tick 2", "tick 3"]);
+
+    // Globals see the same closure, its state carries on
+    assert_eq!(vm.eval("tick()").unwrap(), Value::Number(4.0));
+    assert_eq!(calls.get(), 4);
+}
+
+#[test]
+fn arity_is_checked_before_the_closure_runs() {
+    let mut vm = Vm::new();
+
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    vm.register_native("tick", 1, move |_, _, _| {
+        counter.set(counter.get() + 1);
+        Ok(Value::NULL)
+    });
+
+    for source in ["tick();", "tick(1, 2);"] {
+        let err = vm.run(source).unwrap_err();
+        assert_eq!(err.errors[0].code(), "E0305", "{}", source);
+    }
+    let err = vm.run("tick();").unwrap_err();
+    assert_eq!(err.errors[0].message(), "expected 1 arguments but got 0");
+    assert_eq!(calls.get(), 0);
+}
@@ -0,0 +1,173 @@
+// Spans are byte offsets into the source, so slicing the source with a token's span has to give
+// back exactly the text of the token, whatever chars came before it. A lossless scan has to give
+// back the whole source
+use roxy::scanner::Scanner;
+use roxy::Token;
+
+fn scan(source: &str) -> Vec<Token> {
+    Scanner::new(source.to_string())
+        .scan_tokens()
+        .unwrap()
+        .clone()
+}
+
+fn lexemes(source: &str) -> Vec<&str> {
+    scan(source)
+        .iter()
+        .map(|token| &source[token.span().start..token.span().end])
+        .collect()
+}
+
+#[test]
+fn spans_slice_the_source() {
+    assert_eq!(
+        lexemes("var x = \"héllo\" + \"日本語\";"),
+        vec!["var", "x", "=", "\"héllo\"", "+", "\"日本語\"", ";", ""]
+    );
+    assert_eq!(
+        lexemes("print \"🦀\"; // ünïcode comment\nprint 1.5;"),
+        vec!["print", "\"🦀\"", ";", "print", "1.5", ";", ""]
+    );
+
+    // Escapes are in the lexeme as written
+    assert_eq!(
+        lexemes("\"a\\\"b\\u{e9}\" r\"\\n\" \"\"\"x\ny\"\"\""),
+        vec!["\"a\\\"b\\u{e9}\"", "r\"\\n\"", "\"\"\"x\ny\"\"\"", ""]
+    );
+
+    // Interpolated strings are split around the tokens of each expression
+    assert_eq!(
+        lexemes("\"a ${b + {}} ${\"ü\"}\""),
+        vec!["\"a ${", "b", "+", "{", "}", "} ${", "\"ü\"", "}\"", ""]
+    );
+}
+
+#[test]
+fn columns_count_chars() {
+    let tokens = scan("\"日本\" x\n  y");
+
+    let x = tokens[1].span();
+    assert_eq!((x.start, x.line, x.column), (9, 1, 6));
+
+    let y = tokens[2].span();
+    assert_eq!((y.start, y.line, y.column), (13, 2, 3));
+}
+
+#[test]
+fn empty_source_is_just_eof() {
+    for source in ["", "// only a comment", "   \n\t"] {
+        let tokens = scan(source);
+        assert_eq!(tokens.len(), 1, "{:?}", source);
+        assert_eq!(tokens[0].span().start, source.len());
+    }
+}
+
+#[test]
+fn tokens_are_scanned_lazily() {
+    // Only as much of the source as was asked for gets scanned, the error further on is never hit
+    let mut scanner = Scanner::new("var x = 1; \"unterminated".to_string());
+    let first: Vec<String> = scanner
+        .by_ref()
+        .take(3)
+        .map(|token| token.unwrap().lexeme().to_string())
+        .collect();
+    assert_eq!(first, vec!["var", "x", "="]);
+
+    // The first error ends the stream
+    let rest: Vec<bool> = scanner.map(|token| token.is_ok()).collect();
+    assert_eq!(rest, vec![true, true, false]);
+}
+
+#[test]
+fn eof_ends_the_stream() {
+    let mut scanner = Scanner::new("1".to_string());
+    assert!(scanner.next().is_some_and(|token| token.is_ok()));
+    assert!(scanner.next().is_some_and(|token| token.is_ok()));
+    assert!(scanner.next().is_none());
+}
+
+fn lossless(source: &str) -> Vec<Token> {
+    Scanner::new(source.to_string())
+        .with_trivia(true)
+        .map(|token| token.unwrap())
+        .collect()
+}
+
+fn reconstruct(source: &str) -> String {
+    lossless(source).iter().map(Token::full_text).collect()
+}
+
+#[test]
+fn lossless_scan_gives_back_the_source() {
+    let sources = [
+        "",
+        "  \n\t\r\n",
+        "var x = 1;   // trailing\n\n/* block /* nested */ */ print x;\n",
+        "print \"a ${ b /* c */ } d\";  \r\n// last line without newline",
+        "fun f() {\n    return 0xff_ff + 1e3;\n}",
+    ];
+    for source in sources {
+        assert_eq!(reconstruct(source), source);
+    }
+
+    let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut dirs = vec![root.join("examples"), root.join("tests").join("lox")];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if path.extension().is_none_or(|ext| ext != "rx") {
+                continue;
+            }
+
+            // Programs with scan errors don't make it to the end of the stream
+            let source = std::fs::read_to_string(&path).unwrap();
+            let complete = Scanner::new(source.clone()).scan_tokens().is_ok();
+            if complete {
+                assert_eq!(reconstruct(&source), source, "{}", path.display());
+            }
+        }
+    }
+}
+
+#[test]
+fn trivia_is_attached_to_neighbouring_tokens() {
+    use roxy::tokens::TriviaKind;
+
+    let tokens = lossless("a; // one\n\n// two\nb");
+    let kinds = |trivia: &[roxy::tokens::Trivia]| -> Vec<TriviaKind> {
+        trivia.iter().map(|trivia| trivia.kind).collect()
+    };
+
+    // Up to the end of the line belongs to the token before
+    assert_eq!(
+        kinds(tokens[1].trailing_trivia()),
+        vec![
+            TriviaKind::Whitespace,
+            TriviaKind::LineComment,
+            TriviaKind::Newline
+        ]
+    );
+    assert_eq!(tokens[1].trailing_trivia()[1].text, "// one");
+
+    // The rest leads into the next token
+    assert_eq!(
+        kinds(tokens[2].leading_trivia()),
+        vec![
+            TriviaKind::Newline,
+            TriviaKind::LineComment,
+            TriviaKind::Newline
+        ]
+    );
+    assert_eq!(tokens[2].lexeme(), "b");
+
+    // Without trivia the tokens are the same, just bare
+    let bare = scan("a; // one\n\n// two\nb");
+    assert_eq!(bare.len(), tokens.len());
+    assert!(bare
+        .iter()
+        .all(|token| token.leading_trivia().is_empty() && token.trailing_trivia().is_empty()));
+}
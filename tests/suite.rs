@@ -0,0 +1,236 @@
+// Runs every `.rx` program under `tests/lox/<feature>/` and `examples/` through the roxy binary and
+// checks what it prints against expectations written in comments, in the format of the Crafting
+// Interpreters test suite:
+//
+// print 1 + 2;  // expect: 3
+// print 1 / 0;  // expect runtime error: division by zero
+// var a = a;    // Error at 'a': can't read local variable `a` in its own initializer
+// // [line 7] Error at end: expected `}`
+//
+// Errors are expected on the line of their comment unless a `[line N]` prefix says otherwise.
+// Every feature directory gets a line in the summary, the test fails if any program does. A program
+// has to expect something, one which would pass by printing nothing fails. Files in nested
+// directories are only there to be imported and aren't run on their own
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const EXPECT: &str = "// expect: ";
+const EXPECT_RUNTIME_ERROR: &str = "// expect runtime error: ";
+
+#[derive(Debug, Default)]
+struct Expectations {
+    output: Vec<String>,
+    // Formatted like `[line 3] Error at 'a': message`
+    compile_errors: Vec<String>,
+    // Formatted like `[line 3] message`
+    runtime_error: Option<String>,
+}
+
+impl Expectations {
+    fn parse(source: &str) -> Self {
+        let mut expectations = Expectations::default();
+
+        for (i, line) in source.lines().enumerate() {
+            let line_no = i + 1;
+
+            if let Some(pos) = line.find(EXPECT_RUNTIME_ERROR) {
+                let message = &line[pos + EXPECT_RUNTIME_ERROR.len()..];
+                expectations.runtime_error = Some(format!("[line {}] {}", line_no, message));
+            } else if let Some(pos) = line.find(EXPECT) {
+                expectations
+                    .output
+                    .push(line[pos + EXPECT.len()..].to_string());
+            } else if let Some(error) = Expectations::parse_compile_error(line, line_no) {
+                expectations.compile_errors.push(error);
+            }
+        }
+
+        expectations
+    }
+
+    fn parse_compile_error(line: &str, line_no: usize) -> Option<String> {
+        let comment = &line[line.find("//")? + 2..].trim_start();
+
+        if let Some(rest) = comment.strip_prefix("[line ") {
+            let (line_no, error) = rest.split_once("] ")?;
+            if error.starts_with("Error") {
+                return Some(format!("[line {}] {}", line_no, error));
+            }
+        }
+
+        if comment.starts_with("Error at ") || comment.starts_with("Error: ") {
+            return Some(format!("[line {}] {}", line_no, comment));
+        }
+
+        None
+    }
+
+    fn is_empty(&self) -> bool {
+        self.output.is_empty() && self.compile_errors.is_empty() && self.runtime_error.is_none()
+    }
+
+    fn exit_code(&self) -> i32 {
+        if !self.compile_errors.is_empty() {
+            65
+        } else if self.runtime_error.is_some() {
+            70
+        } else {
+            0
+        }
+    }
+}
+
+// Turns one line of `--error-format=json` output into the format used by the expectations
+fn format_error(source: &str, line: &str) -> Result<(bool, String), String> {
+    let error: serde_json::Value =
+        serde_json::from_str(line).map_err(|err| format!("invalid json `{}`: {}", line, err))?;
+
+    let kind = error["kind"].as_str().unwrap_or_default();
+    let message = error["message"].as_str().unwrap_or_default();
+    let line_no = error["start"]["line"].as_u64().unwrap_or_default();
+
+    match kind {
+        "InterpreterError" | "EnvironmentError" | "InternalError" => {
+            Ok((true, format!("[line {}] {}", line_no, message)))
+        }
+        "SyntaxError" => Ok((false, format!("[line {}] Error: {}", line_no, message))),
+        _ => {
+            let start = error["start"]["offset"].as_u64().unwrap_or_default() as usize;
+            let end = error["end"]["offset"].as_u64().unwrap_or_default() as usize;
+            let location = match source.get(start..end) {
+                Some(lexeme) if !lexeme.is_empty() => format!("at '{}'", lexeme),
+                _ => "at end".to_string(),
+            };
+
+            Ok((
+                false,
+                format!("[line {}] Error {}: {}", line_no, location, message),
+            ))
+        }
+    }
+}
+
+// Every mismatch between what the program did and what its comments expect
+fn run_test(path: &Path) -> Vec<String> {
+    let source = fs::read_to_string(path).unwrap();
+    let expectations = Expectations::parse(&source);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_roxy"))
+        .arg("--error-format=json")
+        .arg(path)
+        .output()
+        .unwrap();
+
+    let mut failures = vec![];
+    if expectations.is_empty() {
+        failures.push("expects no output and no errors, nothing is checked".to_string());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let actual_output: Vec<&str> = stdout.lines().collect();
+    for (i, expected) in expectations.output.iter().enumerate() {
+        match actual_output.get(i) {
+            Some(actual) if actual == expected => {}
+            Some(actual) => {
+                failures.push(format!("expected output `{}` got `{}`", expected, actual))
+            }
+            None => failures.push(format!("missing expected output `{}`", expected)),
+        }
+    }
+    for extra in actual_output.iter().skip(expectations.output.len()) {
+        failures.push(format!("unexpected output `{}`", extra));
+    }
+
+    let mut compile_errors = vec![];
+    let mut runtime_error = None;
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        match format_error(&source, line) {
+            Ok((true, error)) => runtime_error = Some(error),
+            Ok((false, error)) => compile_errors.push(error),
+            Err(err) => failures.push(err),
+        }
+    }
+
+    if compile_errors != expectations.compile_errors {
+        failures.push(format!(
+            "expected compile errors {:?} got {:?}",
+            expectations.compile_errors, compile_errors
+        ));
+    }
+
+    if runtime_error != expectations.runtime_error {
+        failures.push(format!(
+            "expected runtime error {:?} got {:?}",
+            expectations.runtime_error, runtime_error
+        ));
+    }
+
+    let exit_code = output.status.code().unwrap_or(-1);
+    if exit_code != expectations.exit_code() {
+        failures.push(format!(
+            "expected exit code {} got {}",
+            expectations.exit_code(),
+            exit_code
+        ));
+    }
+
+    failures
+}
+
+fn programs_in(dir: &Path) -> Vec<PathBuf> {
+    let mut programs: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "rx"))
+        .collect();
+    programs.sort();
+
+    programs
+}
+
+#[test]
+fn lox_programs() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    // Feature name -> programs testing it
+    let mut features = BTreeMap::new();
+    features.insert("examples".to_string(), programs_in(&root.join("examples")));
+    for entry in fs::read_dir(root.join("tests").join("lox")).unwrap() {
+        let dir = entry.unwrap().path();
+        if dir.is_dir() {
+            let feature = dir.file_name().unwrap().to_string_lossy().to_string();
+            features.insert(feature, programs_in(&dir));
+        }
+    }
+
+    let mut failed = 0;
+    let mut summary = vec![];
+    for (feature, programs) in &features {
+        let mut passed = 0;
+        for program in programs {
+            let failures = run_test(program);
+            if failures.is_empty() {
+                passed += 1;
+                continue;
+            }
+
+            failed += 1;
+            println!("FAIL {}", program.strip_prefix(root).unwrap().display());
+            for failure in failures {
+                println!("     {}", failure);
+            }
+        }
+
+        summary.push(format!(
+            "{:<16} {}/{} passed",
+            feature,
+            passed,
+            programs.len()
+        ));
+    }
+
+    println!("\n{}", summary.join("\n"));
+    assert_eq!(failed, 0, "{} lox programs failed", failed);
+}
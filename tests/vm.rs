@@ -0,0 +1,121 @@
+// The embedding API, values and errors are handed to Rust code which unwraps and compares them
+use roxy::vm::{Value, Vm};
+
+#[test]
+fn values_which_hold_themselves_can_be_debug_printed() {
+    let mut vm = Vm::new();
+
+    vm.run("var xs = [1]; xs.push(xs);").unwrap();
+    let list = vm.get_global("xs").unwrap();
+    assert_eq!(format!("{:?}", list), "List([1, [...]])");
+
+    vm.run("var m = {}; m[\"self\"] = m;").unwrap();
+    let map = vm.get_global("m").unwrap();
+    assert_eq!(format!("{:?}", map), "Map({\"self\": {...}})");
+
+    vm.run(
+        "class Node { init(parent) { this.parent = parent; this.child = nil; } }
+         var parent = Node(nil);
+         parent.child = Node(parent);",
+    )
+    .unwrap();
+    let parent = vm.get_global("parent").unwrap();
+    assert_eq!(format!("{:?}", parent), "RoxyInstance(Node)");
+}
+
+#[test]
+fn errors_holding_such_values_can_be_debug_printed() {
+    let mut vm = Vm::new();
+    let err = vm
+        .run("class N { init() { this.s = this; } } throw N();")
+        .unwrap_err();
+
+    // What `unwrap` would print
+    let debug = format!("{:?}", err);
+    assert!(debug.contains("Thrown(RoxyInstance(N)"), "{}", debug);
+}
+
+#[test]
+fn lox_functions_can_be_called_with_rust_values() {
+    let mut vm = Vm::new();
+    vm.run("fun greet(name, times) { var out = \"\"; for (var i = 0; i < times; i = i + 1) out = out + name; return out; }")
+        .unwrap();
+
+    let result = vm
+        .call_function("greet", vec!["ab".into(), 3.into()])
+        .unwrap();
+    assert_eq!(String::try_from(result).unwrap(), "ababab");
+
+    vm.run("fun add(a, b) { return a + b; }").unwrap();
+    let sum = vm
+        .call_function("add", vec![1.5.into(), 2i64.into()])
+        .unwrap();
+    assert_eq!(f64::try_from(sum).unwrap(), 3.5);
+}
+
+#[test]
+fn calls_with_the_wrong_number_of_arguments_fail() {
+    let mut vm = Vm::new();
+    vm.run("fun add(a, b) { return a + b; }").unwrap();
+
+    let err = vm.call_function("add", vec![1.into()]).unwrap_err();
+    assert_eq!(err.errors[0].code(), "E0305");
+    assert_eq!(err.errors[0].message(), "expected 2 arguments but got 1");
+}
+
+#[test]
+fn calls_to_undefined_functions_fail() {
+    let mut vm = Vm::new();
+
+    let err = vm.call_function("missing", vec![]).unwrap_err();
+    assert_eq!(err.errors[0].code(), "E0314");
+    assert_eq!(err.errors[0].message(), "undefined variable `missing`");
+}
+
+#[test]
+fn globals_set_from_rust_are_seen_by_scripts() {
+    let mut vm = Vm::new();
+    vm.set_global("limit", 10);
+    vm.set_global("name", "roxy");
+    vm.set_global("scores", vec![1, 2, 3]);
+
+    assert_eq!(vm.eval("limit * 2").unwrap(), Value::Number(20.0));
+    assert_eq!(vm.eval("name + \"!\"").unwrap(), Value::from("roxy!"));
+    assert_eq!(vm.eval("scores.len()").unwrap(), Value::Number(3.0));
+
+    // Setting it again replaces the value
+    vm.set_global("limit", 1);
+    assert_eq!(vm.eval("limit").unwrap(), Value::Number(1.0));
+
+    vm.run("var fromScript = limit + 1;").unwrap();
+    assert_eq!(vm.get_global("fromScript"), Some(Value::Number(2.0)));
+    assert_eq!(vm.get_global("neverDefined"), None);
+}
+
+#[test]
+fn rust_values_convert_both_ways() {
+    assert_eq!(f64::try_from(Value::from(1.5)), Ok(1.5));
+    assert_eq!(f64::try_from(Value::from(7i64)), Ok(7.0));
+    assert_eq!(f64::try_from(Value::from(-3i32)), Ok(-3.0));
+    assert_eq!(bool::try_from(Value::from(true)), Ok(true));
+    assert_eq!(
+        String::try_from(Value::from("text".to_string())),
+        Ok("text".to_string())
+    );
+    assert_eq!(
+        String::try_from(Value::from("text")),
+        Ok("text".to_string())
+    );
+
+    assert_eq!(Value::from(()), Value::NULL);
+    assert_eq!(Value::from(None::<f64>), Value::NULL);
+    assert_eq!(Value::from(Some(2.0)), Value::Number(2.0));
+
+    let list = Value::from(vec!["a", "b"]);
+    assert_eq!(list.to_string(), "[\"a\", \"b\"]");
+
+    // A value of another type is handed back
+    assert_eq!(f64::try_from(Value::from("1")), Err(Value::from("1")));
+    assert_eq!(bool::try_from(Value::NULL), Err(Value::NULL));
+    assert_eq!(String::try_from(Value::from(1.0)), Err(Value::Number(1.0)));
+}